@@ -0,0 +1,296 @@
+/// 采集请求的认证方式，获取到的token会被缓存，过期或者请求返回401时重新获取
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use tracing::debug;
+
+use crate::http::ConnectionConfig;
+use crate::json::find_value;
+
+/// 认证配置
+/// ```json
+/// {"type": "oauth2_client_credentials", "token_url": "https://x/oauth/token", "client_id": "id", "client_secret": "secret"}
+/// {"type": "login", "url": "https://x/login", "body": "{\"user\":\"a\"}", "token_path": "data.token"}
+/// {"type": "hmac", "key_id": "id", "secret": "secret"}
+/// {"type": "basic", "username": "a", "password": "b"}
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthConfig {
+    OAuth2ClientCredentials {
+        token_url: String,
+        client_id: String,
+        client_secret: String,
+        scope: Option<String>,
+    },
+    /// 先请求登录接口，再从返回数据的token_path中取出token
+    Login {
+        url: String,
+        /// 默认为POST
+        method: Option<String>,
+        headers: Option<HashMap<String, String>>,
+        body: Option<String>,
+        token_path: String,
+        /// 返回数据中有效期（秒）的路径
+        expires_in_path: Option<String>,
+        /// 未返回有效期时使用的有效期（秒），都未配置时只在请求返回401时重新登录
+        expires_in: Option<u64>,
+        /// 携带token的请求头，默认为Authorization
+        header_name: Option<String>,
+        /// 请求头的值模板，默认为`Bearer {token}`
+        header_template: Option<String>,
+    },
+    /// 对请求进行HMAC-SHA256签名，签名字符串为：
+    /// `{method}\n{url}\n{timestamp}\n{nonce}\n{body}`，签名结果使用base64编码
+    Hmac {
+        key_id: String,
+        secret: String,
+        /// 默认为X-Key-Id
+        key_id_header: Option<String>,
+        /// 默认为X-Signature
+        signature_header: Option<String>,
+        /// 默认为X-Timestamp
+        timestamp_header: Option<String>,
+        /// 默认为X-Nonce
+        nonce_header: Option<String>,
+    },
+    Basic {
+        username: String,
+        password: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    header: (String, String),
+    expires_at: Option<Instant>,
+}
+
+/// 认证信息提供者，同一个认证配置应共用一个实例以复用缓存的token
+pub struct AuthProvider {
+    config: AuthConfig,
+    token: Mutex<Option<CachedToken>>,
+}
+
+impl fmt::Debug for AuthProvider {
+    /// 不输出认证配置，避免密钥被打印到日志中
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let auth_type = match self.config {
+            AuthConfig::OAuth2ClientCredentials { .. } => "oauth2_client_credentials",
+            AuthConfig::Login { .. } => "login",
+            AuthConfig::Hmac { .. } => "hmac",
+            AuthConfig::Basic { .. } => "basic",
+        };
+        f.debug_struct("AuthProvider")
+            .field("type", &auth_type)
+            .finish()
+    }
+}
+
+/// token在过期前提前刷新的时间
+const REFRESH_AHEAD: Duration = Duration::from_secs(30);
+
+impl AuthProvider {
+    pub fn new(config: AuthConfig) -> Self {
+        Self {
+            config,
+            token: Mutex::new(None),
+        }
+    }
+
+    /// 清除缓存的token，下次请求时重新获取
+    pub fn invalidate(&self) {
+        *self.token.lock().unwrap() = None;
+    }
+
+    /// 获取本次请求需要携带的认证请求头，获取token时使用与数据请求相同的连接配置
+    pub async fn headers(
+        &self,
+        method: &Method,
+        url: &str,
+        body: Option<&str>,
+        connection: &ConnectionConfig,
+    ) -> Result<Vec<(String, String)>> {
+        match &self.config {
+            AuthConfig::Basic { username, password } => {
+                let value = BASE64_STANDARD.encode(format!("{username}:{password}"));
+                Ok(vec![(
+                    "Authorization".to_string(),
+                    format!("Basic {value}"),
+                )])
+            }
+            AuthConfig::Hmac {
+                key_id,
+                secret,
+                key_id_header,
+                signature_header,
+                timestamp_header,
+                nonce_header,
+            } => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)?
+                    .as_millis()
+                    .to_string();
+                let nonce = uuid::Uuid::new_v4().simple().to_string();
+                let sign_str = format!(
+                    "{}\n{}\n{}\n{}\n{}",
+                    method.as_str(),
+                    url,
+                    timestamp,
+                    nonce,
+                    body.unwrap_or_default()
+                );
+                let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+                    .map_err(|err| anyhow!("HMAC密钥无效: {err}"))?;
+                mac.update(sign_str.as_bytes());
+                let signature = BASE64_STANDARD.encode(mac.finalize().into_bytes());
+
+                Ok(vec![
+                    (
+                        key_id_header.clone().unwrap_or("X-Key-Id".to_string()),
+                        key_id.clone(),
+                    ),
+                    (
+                        signature_header
+                            .clone()
+                            .unwrap_or("X-Signature".to_string()),
+                        signature,
+                    ),
+                    (
+                        timestamp_header
+                            .clone()
+                            .unwrap_or("X-Timestamp".to_string()),
+                        timestamp,
+                    ),
+                    (nonce_header.clone().unwrap_or("X-Nonce".to_string()), nonce),
+                ])
+            }
+            _ => Ok(vec![self.token_header(connection).await?]),
+        }
+    }
+
+    async fn token_header(&self, connection: &ConnectionConfig) -> Result<(String, String)> {
+        if let Some(token) = self.token.lock().unwrap().as_ref() {
+            let expired = token
+                .expires_at
+                .is_some_and(|x| Instant::now() + REFRESH_AHEAD >= x);
+            if !expired {
+                return Ok(token.header.clone());
+            }
+        }
+
+        let token = self.fetch_token(connection).await?;
+        let header = token.header.clone();
+        *self.token.lock().unwrap() = Some(token);
+
+        Ok(header)
+    }
+
+    async fn fetch_token(&self, connection: &ConnectionConfig) -> Result<CachedToken> {
+        let client = reqwest::Client::builder().timeout(Duration::from_secs(30));
+        let client = connection.apply(client)?.build()?;
+
+        match &self.config {
+            AuthConfig::OAuth2ClientCredentials {
+                token_url,
+                client_id,
+                client_secret,
+                scope,
+            } => {
+                debug!("获取OAuth2 token: {token_url}");
+                let mut form = vec![
+                    ("grant_type", "client_credentials"),
+                    ("client_id", client_id.as_str()),
+                    ("client_secret", client_secret.as_str()),
+                ];
+                if let Some(scope) = scope {
+                    form.push(("scope", scope.as_str()));
+                }
+                let res = client.post(token_url).form(&form).send().await?;
+                let status = res.status();
+                let text = res.text().await?;
+                if !status.is_success() {
+                    return Err(anyhow!("获取OAuth2 token失败 {status}: {text}"));
+                }
+                let data: Value = serde_json::from_str(&text)
+                    .map_err(|err| anyhow!("OAuth2 token返回的数据 {text} 无法被序列化 {err}"))?;
+                let token = data["access_token"]
+                    .as_str()
+                    .ok_or(anyhow!("OAuth2 token返回的数据中没有access_token: {text}"))?;
+
+                Ok(CachedToken {
+                    header: ("Authorization".to_string(), format!("Bearer {token}")),
+                    expires_at: data["expires_in"]
+                        .as_u64()
+                        .map(|x| Instant::now() + Duration::from_secs(x)),
+                })
+            }
+            AuthConfig::Login {
+                url,
+                method,
+                headers,
+                body,
+                token_path,
+                expires_in_path,
+                expires_in,
+                header_name,
+                header_template,
+            } => {
+                debug!("请求登录接口获取token: {url}");
+                let method: Method = method.as_deref().unwrap_or("POST").parse()?;
+                let mut request = client.request(method, url);
+                for (key, value) in headers.iter().flatten() {
+                    request = request.header(key, value);
+                }
+                if let Some(body) = body {
+                    request = request.body(body.clone());
+                }
+                let res = request.send().await?;
+                let status = res.status();
+                let text = res.text().await?;
+                if !status.is_success() {
+                    return Err(anyhow!("登录接口请求失败 {status}: {text}"));
+                }
+                let data: Value = serde_json::from_str(&text)
+                    .map_err(|err| anyhow!("登录接口返回的数据 {text} 无法被序列化 {err}"))?;
+                let token = find_value(token_path, &data, false)
+                    .map_err(|_| anyhow!("登录接口返回的数据中未找到token: {token_path}"))?;
+                let token = match token.as_str() {
+                    Some(x) => x.to_string(),
+                    None => token.to_string(),
+                };
+                let expires_in = expires_in_path
+                    .as_ref()
+                    .and_then(|x| find_value(x, &data, false).ok())
+                    .and_then(|x| match x.as_str() {
+                        Some(s) => s.parse::<u64>().ok(),
+                        None => x.as_u64(),
+                    })
+                    .or(*expires_in);
+                let header_value = header_template
+                    .as_deref()
+                    .unwrap_or("Bearer {token}")
+                    .replace("{token}", &token);
+
+                Ok(CachedToken {
+                    header: (
+                        header_name.clone().unwrap_or("Authorization".to_string()),
+                        header_value,
+                    ),
+                    expires_at: expires_in.map(|x| Instant::now() + Duration::from_secs(x)),
+                })
+            }
+            _ => Err(anyhow!("该认证方式不需要获取token")),
+        }
+    }
+}
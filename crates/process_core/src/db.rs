@@ -6,9 +6,10 @@ use process_jdbc::common::{ExecuteJDBC, JDBC};
 use process_jdbc::kingbase::Kingbase;
 use process_jdbc::mssql::MSSQL;
 use process_jdbc::oracle::Oracle;
-use sea_orm::{ConnectionTrait, FromQueryResult, JsonValue, Statement};
+use sea_orm::{ConnectionTrait, FromQueryResult, JsonValue, Statement, TransactionTrait};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use tracing::{debug, warn};
 
 use crate::http::generate_sql_list;
@@ -77,6 +78,194 @@ impl Db {
 
         self
     }
+
+    /// 按template_string生成需要在目标数据库中执行的语句
+    pub fn sql_list(&self) -> Result<Vec<String>> {
+        let data = self.data.as_ref().ok_or(anyhow!("self.data 中没有数据"))?;
+
+        let template_sql = self
+            .template_string
+            .as_ref()
+            .ok_or(anyhow!("未设置template_string"))?;
+
+        if !template_sql.to_lowercase().contains("insert into ") {
+            return Err(anyhow!("这条语句不是插入语句！"));
+        }
+
+        generate_sql_list(template_sql, data)
+    }
+}
+
+impl Database {
+    /// 限制查询返回的行数，用于同步前预览
+    /// MSSQL中带ORDER BY的语句使用OFFSET FETCH，派生表中不能直接包含ORDER BY
+    pub fn limit_sql(&self, query_sql: &str, limit: u64) -> String {
+        let query_sql = trim_sql(query_sql);
+        match self {
+            Database::MSSQL => match mssql_order_by(query_sql) {
+                Some(_) => format!("{query_sql} OFFSET 0 ROWS FETCH NEXT {limit} ROWS ONLY"),
+                None => format!("SELECT TOP {limit} * FROM ({query_sql}) t"),
+            },
+            Database::ORACLE => format!("SELECT * FROM ({query_sql}) WHERE ROWNUM <= {limit}"),
+            _ => format!("SELECT * FROM ({query_sql}) t LIMIT {limit}"),
+        }
+    }
+
+    /// 统计查询语句返回的行数
+    pub fn count_sql(&self, query_sql: &str) -> String {
+        format!(
+            "SELECT COUNT(*) AS total FROM ({}) t",
+            self.derived_sql(query_sql)
+        )
+    }
+
+    /// 只查询指定的列
+    pub fn select_columns_sql(&self, query_sql: &str, columns: &[String]) -> String {
+        format!(
+            "SELECT {} FROM ({}) t",
+            columns.join(", "),
+            self.derived_sql(query_sql)
+        )
+    }
+
+    /// 作为派生表的语句，MSSQL中去掉最外层的ORDER BY，统计与只查询部分列时不需要排序
+    fn derived_sql<'a>(&self, query_sql: &'a str) -> &'a str {
+        let query_sql = trim_sql(query_sql);
+        match (self, mssql_order_by(query_sql)) {
+            (Database::MSSQL, Some((sql, _))) => sql,
+            _ => query_sql,
+        }
+    }
+}
+
+fn trim_sql(query_sql: &str) -> &str {
+    query_sql.trim().trim_end_matches(';').trim_end()
+}
+
+/// MSSQL中只有同时使用TOP或者OFFSET时派生表才能包含ORDER BY，
+/// 其他情况返回去掉最外层ORDER BY后的语句与ORDER BY子句
+fn mssql_order_by(query_sql: &str) -> Option<(&str, &str)> {
+    let (sql, order_by) = split_order_by(query_sql)?;
+    if order_by.to_uppercase().contains("OFFSET") {
+        return None;
+    }
+    let words = sql
+        .split_whitespace()
+        .take(3)
+        .map(|x| x.to_uppercase())
+        .collect::<Vec<String>>();
+    match words.iter().map(|x| x.as_str()).collect::<Vec<&str>>()[..] {
+        ["SELECT", "TOP", ..] | ["SELECT", "DISTINCT", "TOP"] => None,
+        _ => Some((sql, order_by)),
+    }
+}
+
+/// 查找不在括号与引号中的最后一个ORDER BY，返回之前的语句与ORDER BY子句
+fn split_order_by(query_sql: &str) -> Option<(&str, &str)> {
+    let bytes = query_sql.as_bytes();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut found = None;
+    for (i, c) in bytes.iter().enumerate() {
+        match quote {
+            Some(end) => {
+                if *c == end {
+                    quote = None;
+                }
+            }
+            None => match c {
+                b'\'' | b'"' | b'`' => quote = Some(*c),
+                b'[' => quote = Some(b']'),
+                b'(' => depth += 1,
+                b')' => depth -= 1,
+                _ if depth == 0
+                    && bytes[i..].len() > 5
+                    && bytes[i..i + 5].eq_ignore_ascii_case(b"order")
+                    && (i == 0 || !is_word_byte(bytes[i - 1])) =>
+                {
+                    let rest = &query_sql[i + 5..];
+                    let by = rest.trim_start();
+                    if by.len() < rest.len()
+                        && by.len() > 2
+                        && by.as_bytes()[..2].eq_ignore_ascii_case(b"by")
+                        && !is_word_byte(by.as_bytes()[2])
+                    {
+                        found = Some(i);
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    found.map(|i| (query_sql[..i].trim_end(), &query_sql[i..]))
+}
+
+fn is_word_byte(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+/// 解析count_sql的查询结果，不同数据库返回的列名大小写与数值类型不一致，只取第一列
+pub fn parse_count(rows: &[Value]) -> Result<u64> {
+    let value = rows
+        .first()
+        .and_then(|x| x.as_object())
+        .and_then(|x| x.values().next())
+        .ok_or(anyhow!("统计结果为空"))?;
+
+    match value {
+        Value::Number(x) => x
+            .as_u64()
+            .or(x.as_f64().map(|x| x as u64))
+            .ok_or(anyhow!("统计结果无法解析: {value}")),
+        Value::String(x) => x
+            .trim()
+            .parse::<f64>()
+            .map(|x| x as u64)
+            .map_err(|_| anyhow!("统计结果无法解析: {value}")),
+        _ => Err(anyhow!("统计结果无法解析: {value}")),
+    }
+}
+
+/// 统计查询语句在数据源中返回的行数
+pub async fn count_rows(db_source: &DataSource, query_sql: &str) -> Result<u64> {
+    let rows = find_all_sql(db_source, db_source.database_type.count_sql(query_sql)).await?;
+    parse_count(&rows)
+}
+
+/// 按指定列计算数据的校验和，与行的顺序无关
+/// 列名不区分大小写；数值与字符串统一转成字符串后比较，null视为空字符串
+pub fn checksum_rows(rows: &[Value], key_columns: &[String]) -> Result<String> {
+    let mut keys = vec![];
+    for row in rows {
+        let obj = row.as_object().ok_or(anyhow!("数据不是对象: {row}"))?;
+        let mut values = vec![];
+        for column in key_columns {
+            let value = obj
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(column))
+                .map(|(_, value)| value)
+                .ok_or(anyhow!("数据中未找到列 {column}"))?;
+            values.push(match value {
+                Value::String(x) => x.clone(),
+                Value::Null => String::new(),
+                x => x.to_string(),
+            });
+        }
+        keys.push(values.join("\u{1f}"));
+    }
+    keys.sort();
+
+    let mut hasher = Sha256::new();
+    for key in keys {
+        hasher.update(key.as_bytes());
+        hasher.update(b"\n");
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|x| format!("{:02x}", x))
+        .collect())
 }
 
 pub async fn execute_sql(db_source: &DataSource, query_sql_list: Vec<String>) -> Result<()> {
@@ -160,6 +349,167 @@ pub async fn execute_sql(db_source: &DataSource, query_sql_list: Vec<String>) ->
     }
 }
 
+/// 分批执行SQL的结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChunkedExecution {
+    /// 已提交的语句数
+    pub committed: usize,
+    /// 是否在两批之间被取消
+    pub cancelled: bool,
+}
+
+/// 分批执行中途失败，可以通过anyhow::Error::downcast_ref取得失败前已提交的语句数
+#[derive(Debug)]
+pub struct ChunkError {
+    pub committed: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "已提交 {} 条语句，当前批次已回滚: {}",
+            self.committed, self.message
+        )
+    }
+}
+
+impl std::error::Error for ChunkError {}
+
+fn chunk_error(committed: usize, err: impl std::fmt::Display) -> anyhow::Error {
+    anyhow::Error::new(ChunkError {
+        committed,
+        message: err.to_string(),
+    })
+}
+
+/// 分批执行SQL，每批在同一个事务中执行，执行失败时回滚当前批次；
+/// 每批执行前以已提交的语句数调用is_cancelled，可以用来报告进度，
+/// 返回true时不再执行剩余的批次，已提交的批次不会回滚
+pub async fn execute_sql_in_chunks<F>(
+    db_source: &DataSource,
+    query_sql_list: &[String],
+    chunk_size: usize,
+    is_cancelled: F,
+) -> Result<ChunkedExecution>
+where
+    F: Fn(usize) -> bool,
+{
+    debug!("db_source {:?}", db_source);
+    let password = decode_db_password(&db_source.password);
+    let chunk_size = chunk_size.max(1);
+    match db_source.database_type {
+        Database::POSTGRES | Database::MYSQL => {
+            let scheme = match db_source.database_type {
+                Database::POSTGRES => "postgres",
+                _ => "mysql",
+            };
+            let db_url = format!(
+                "{scheme}://{}:{}@{}:{}/{}",
+                db_source.user, password, db_source.host, db_source.port, db_source.database_name
+            );
+            let db = sea_orm::Database::connect(db_url.as_str()).await?;
+
+            let mut res = ChunkedExecution::default();
+            for chunk in query_sql_list.chunks(chunk_size) {
+                if is_cancelled(res.committed) {
+                    res.cancelled = true;
+                    break;
+                }
+                // 开启、提交、回滚事务失败时也需要带上已提交的语句数
+                let txn = db
+                    .begin()
+                    .await
+                    .map_err(|err| chunk_error(res.committed, err))?;
+                for sql in chunk {
+                    let statement = Statement::from_string(db.get_database_backend(), sql.clone());
+                    if let Err(err) = txn.execute(statement).await {
+                        return Err(match txn.rollback().await {
+                            Ok(_) => chunk_error(res.committed, err),
+                            Err(x) => chunk_error(res.committed, format!("{err}，回滚失败: {x}")),
+                        });
+                    }
+                }
+                txn.commit()
+                    .await
+                    .map_err(|err| chunk_error(res.committed, err))?;
+                res.committed += chunk.len();
+            }
+            Ok(res)
+        }
+        Database::MSSQL => {
+            let db_url = format!(
+                "jdbc:sqlserver://{}:{};databaseName={}",
+                db_source.host, db_source.port, db_source.database_name,
+            );
+            let mut conn = MSSQL::new()?;
+
+            conn.connect(&db_url, db_source.user.as_str(), password.as_str())
+                .map_err(|err| anyhow!("数据库连接失败！: {err}"))?;
+
+            execute_jdbc_in_chunks(&mut conn, query_sql_list, chunk_size, is_cancelled)
+        }
+        Database::ORACLE => {
+            let db_url = format!(
+                "jdbc:oracle:thin:@//{}:{}/{}",
+                db_source.host, db_source.port, db_source.database_name,
+            );
+            let mut conn = Oracle::new()?;
+
+            conn.connect(&db_url, db_source.user.as_str(), password.as_str())
+                .map_err(|err| anyhow!("数据库连接失败！: {err}"))?;
+
+            execute_jdbc_in_chunks(&mut conn, query_sql_list, chunk_size, is_cancelled)
+        }
+        Database::KINGBASE => {
+            let db_url = format!(
+                "jdbc:kingbase8://{}:{}/{}",
+                db_source.host, db_source.port, db_source.database_name,
+            );
+            let mut conn = Kingbase::new()?;
+
+            conn.connect(&db_url, db_source.user.as_str(), password.as_str())
+                .map_err(|err| anyhow!("数据库连接失败！: {err}"))?;
+
+            execute_jdbc_in_chunks(&mut conn, query_sql_list, chunk_size, is_cancelled)
+        }
+    }
+}
+
+fn execute_jdbc_in_chunks<T, F>(
+    conn: &mut T,
+    query_sql_list: &[String],
+    chunk_size: usize,
+    is_cancelled: F,
+) -> Result<ChunkedExecution>
+where
+    T: JDBC + ExecuteJDBC,
+    F: Fn(usize) -> bool,
+{
+    conn.set_auto_commit(false)?;
+
+    let mut res = ChunkedExecution::default();
+    for chunk in query_sql_list.chunks(chunk_size) {
+        if is_cancelled(res.committed) {
+            res.cancelled = true;
+            break;
+        }
+        for sql in chunk {
+            if let Err(err) = conn.execute_update(sql) {
+                return Err(match conn.rollback() {
+                    Ok(_) => chunk_error(res.committed, err),
+                    Err(x) => chunk_error(res.committed, format!("{err}，回滚失败: {x}")),
+                });
+            }
+        }
+        conn.commit()
+            .map_err(|err| chunk_error(res.committed, err))?;
+        res.committed += chunk.len();
+    }
+    Ok(res)
+}
+
 pub async fn find_all_sql(db_source: &DataSource, query_sql: String) -> Result<Vec<Value>> {
     debug!("db_source {:?}", db_source);
     if is_non_query_statement(query_sql.as_str()) {
@@ -316,21 +666,7 @@ impl Export for Db {
     type Target = Result<Vec<String>>;
 
     async fn export(&mut self) -> Self::Target {
-        if self.data.is_none() {
-            return Err(anyhow!("self.data 中没有数据"));
-        }
-        let data = self.data.as_ref().unwrap();
-
-        let template_sql = self
-            .template_string
-            .as_ref()
-            .ok_or(anyhow!("未设置template_string"))?;
-
-        if !template_sql.to_lowercase().contains("insert into ") {
-            return Err(anyhow!("这条语句不是插入语句！"));
-        }
-
-        let sql_list = generate_sql_list(template_sql, data)?;
+        let sql_list = self.sql_list()?;
 
         if let Some(db_source) = &self.target_db_source_config {
             execute_sql(db_source, sql_list.clone()).await?;
@@ -0,0 +1,186 @@
+/// 按行（记录）解析的返回数据格式：NDJSON、CSV/TSV、正则匹配的文本行
+/// 数据以流的方式分块写入，每解析出一条完整的记录就转换为JSON，不需要先将全部返回内容读取为字符串
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde_json::{json, Map, Value};
+
+use crate::http::{ResponseConfig, ResponseFormat};
+
+#[derive(Debug)]
+enum RecordFormat {
+    Ndjson,
+    Csv { delimiter: u8 },
+    Lines { regex: Option<Regex> },
+}
+
+#[derive(Debug)]
+pub struct RecordDecoder {
+    format: RecordFormat,
+    buffer: Vec<u8>,
+    /// buffer中已经扫描过的位置，避免重复扫描
+    scan_index: usize,
+    /// CSV扫描到scan_index时是否处于引号中
+    in_quotes: bool,
+    /// 已读取的记录数，用于错误提示
+    line_number: usize,
+    headers: Option<Vec<String>>,
+    records: Vec<Value>,
+}
+
+impl RecordDecoder {
+    /// 返回None表示该格式需要读取完整的返回内容后再解析
+    pub fn new(config: &ResponseConfig) -> Result<Option<Self>> {
+        let format = match config.format {
+            ResponseFormat::Ndjson => RecordFormat::Ndjson,
+            ResponseFormat::Csv => RecordFormat::Csv {
+                delimiter: csv_delimiter(config.delimiter.unwrap_or(','))?,
+            },
+            ResponseFormat::Tsv => RecordFormat::Csv {
+                delimiter: csv_delimiter(config.delimiter.unwrap_or('\t'))?,
+            },
+            ResponseFormat::Lines => RecordFormat::Lines {
+                regex: match &config.line_regex {
+                    Some(x) => {
+                        Some(Regex::new(x).map_err(|err| anyhow!("line_regex 无法解析: {err}"))?)
+                    }
+                    None => None,
+                },
+            },
+            _ => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            format,
+            buffer: vec![],
+            scan_index: 0,
+            in_quotes: false,
+            line_number: 0,
+            headers: None,
+            records: vec![],
+        }))
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) -> Result<()> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut start = 0;
+        let mut i = self.scan_index;
+        while i < self.buffer.len() {
+            match self.buffer[i] {
+                b'"' if matches!(self.format, RecordFormat::Csv { .. }) => {
+                    self.in_quotes = !self.in_quotes;
+                }
+                b'\n' if !self.in_quotes => {
+                    let record = self.buffer[start..i].to_vec();
+                    self.decode_record(&record)?;
+                    start = i + 1;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+
+        self.buffer.drain(..start);
+        self.scan_index = self.buffer.len();
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<Vec<Value>> {
+        if self.in_quotes {
+            return Err(anyhow!("第{}行CSV数据的引号未闭合", self.line_number + 1));
+        }
+        let record = std::mem::take(&mut self.buffer);
+        self.decode_record(&record)?;
+
+        Ok(self.records)
+    }
+
+    fn decode_record(&mut self, record: &[u8]) -> Result<()> {
+        self.line_number += 1;
+        let mut text = std::str::from_utf8(record)
+            .map_err(|err| anyhow!("第{}行数据不是有效的UTF-8: {err}", self.line_number))?;
+        if self.line_number == 1 {
+            text = text.trim_start_matches('\u{feff}');
+        }
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+
+        match &self.format {
+            RecordFormat::Ndjson => {
+                let value = serde_json::from_str(text).map_err(|err| {
+                    anyhow!("第{}行数据 {text} 无法被序列化: {err}", self.line_number)
+                })?;
+                self.records.push(value);
+            }
+            RecordFormat::Csv { delimiter } => {
+                let fields = split_csv_record(text, *delimiter as char);
+                match &self.headers {
+                    None => self.headers = Some(fields),
+                    Some(headers) => {
+                        let mut row = Map::new();
+                        for (i, key) in headers.iter().enumerate() {
+                            row.insert(key.clone(), json!(fields.get(i)));
+                        }
+                        self.records.push(Value::Object(row));
+                    }
+                }
+            }
+            RecordFormat::Lines { regex } => match regex {
+                None => self.records.push(json!({ "line": text })),
+                Some(regex) => {
+                    // 未匹配的行会被忽略
+                    if let Some(caps) = regex.captures(text) {
+                        let mut row = Map::new();
+                        for name in regex.capture_names().flatten() {
+                            row.insert(
+                                name.to_string(),
+                                json!(caps.name(name).map(|x| x.as_str())),
+                            );
+                        }
+                        if row.is_empty() {
+                            row.insert("line".to_string(), json!(text));
+                        }
+                        self.records.push(Value::Object(row));
+                    }
+                }
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// 按字节查找分隔符，只能使用引号与换行以外的ASCII字符
+fn csv_delimiter(delimiter: char) -> Result<u8> {
+    match delimiter {
+        '"' | '\r' | '\n' => Err(anyhow!("delimiter 不能是引号或者换行")),
+        x if x.is_ascii() => Ok(x as u8),
+        x => Err(anyhow!("delimiter {x} 只能是ASCII字符")),
+    }
+}
+
+/// 按RFC 4180切分一行CSV数据，引号中的分隔符与换行不切分，`""`表示一个引号
+fn split_csv_record(text: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
@@ -1,4 +1,5 @@
 use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tracing::error;
 
@@ -119,6 +120,65 @@ pub fn map_data(origin_data: &Value, map_rules: &Vec<[String; 2]>) -> anyhow::Re
     }
 }
 
+/// map_rules中一条规则的匹配情况
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MapRuleDiagnostic {
+    pub origin: String,
+    pub target: String,
+    /// 匹配到的非空值的数量
+    pub matched: usize,
+    /// 没有匹配到数据的原因
+    pub message: Option<String>,
+}
+
+/// 按map_data的查找方式检查每条规则的原始路径能否匹配到数据
+pub fn check_map_rules(origin_data: &Value, map_rules: &[[String; 2]]) -> Vec<MapRuleDiagnostic> {
+    map_rules
+        .iter()
+        .map(|rule| {
+            let origin = rule[0].as_str();
+            let (matched, message) = match find_value(origin, origin_data, true) {
+                Ok(Value::Array(list)) => match list.iter().filter(|x| !x.is_null()).count() {
+                    // 字段不存在时find_value也会返回null
+                    0 if !has_field(origin, origin_data) => {
+                        (0, Some(format!("{origin} 未匹配到数据")))
+                    }
+                    0 => (0, Some(format!("{origin} 匹配到的值都为空"))),
+                    n => (n, None),
+                },
+                Ok(Value::Null) | Err(_) => (0, Some(format!("{origin} 未匹配到数据"))),
+                Ok(_) => (
+                    0,
+                    Some(format!(
+                        "{origin} 匹配到的值不是数组，映射时会被忽略，请使用#引用数组中的字段"
+                    )),
+                ),
+            };
+
+            MapRuleDiagnostic {
+                origin: rule[0].clone(),
+                target: rule[1].clone(),
+                matched,
+                message,
+            }
+        })
+        .collect()
+}
+
+/// 路径中的字段是否存在，值为null也算存在；经过数组时任意一项中存在即可
+fn has_field(key: &str, value: &Value) -> bool {
+    if let Some(list) = value.as_array() {
+        return list.iter().any(|x| has_field(key, x));
+    }
+
+    match key.find(['.', '#']) {
+        Some(index) => value
+            .get(&key[..index])
+            .is_some_and(|x| has_field(&key[index + 1..], x)),
+        None => value.get(key).is_some(),
+    }
+}
+
 fn get_target_rule_data(o_key: &str, t_key: &str, origin_data: &Value, value: &mut Value) {
     let mut key = t_key;
     let current_key: &str;
@@ -196,67 +256,225 @@ fn get_target_rule_data(o_key: &str, t_key: &str, origin_data: &Value, value: &m
     }
 }
 
+/// 父子结构嵌套数据的展开规则
+/// ```json
+/// {
+///     "root_key": "result.data",
+///     "children_key": "children",
+///     "id_key": "id",
+///     "level_key": "level",
+///     "path_key": "path"
+/// }
+/// ```
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NestedConfig {
+    /// 嵌套数据所在的路径，例如：result.data
+    pub root_key: String,
+    /// 子节点字段，子节点数组在中间对象下时使用`.`连接，例如：sub.items
+    pub children_key: String,
+    /// 同一节点下存在多个子节点字段时，额外的子节点字段
+    #[serde(default)]
+    pub extra_children_keys: Vec<String>,
+    pub id_key: String,
+    /// 父节点id输出字段，默认为 parent_{id_key}
+    pub parent_key: Option<String>,
+    /// 节点层级输出字段，根节点为1
+    pub level_key: Option<String>,
+    /// 根节点id输出字段
+    pub root_id_key: Option<String>,
+    /// 完整路径输出字段，例如：1/4/7
+    pub path_key: Option<String>,
+    /// 完整路径的分隔符，默认为`/`
+    pub path_separator: Option<String>,
+    /// 是否为叶子节点输出字段
+    pub leaf_key: Option<String>,
+    /// 同级节点中的顺序输出字段，从1开始
+    pub order_key: Option<String>,
+}
+
+impl NestedConfig {
+    pub fn new(root_key: &str, children_key: &str, id_key: &str) -> Self {
+        Self {
+            root_key: root_key.to_string(),
+            children_key: children_key.to_string(),
+            id_key: id_key.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn children_keys(&self) -> Vec<&str> {
+        let mut keys = vec![self.children_key.as_str()];
+        keys.extend(self.extra_children_keys.iter().map(|x| x.as_str()));
+        keys
+    }
+}
+
+/// 展开嵌套数据时父节点传递给子节点的信息
+struct NestedParent<'a> {
+    id: &'a Value,
+    root_id: &'a Value,
+    path: &'a str,
+    level: usize,
+}
+
 pub fn flat_nested_object(
     value: &Value,
     root_key: &str,
     children_key: &str,
     id_key: &str,
 ) -> anyhow::Result<Value> {
-    let root_value = find_value(root_key, value, true);
+    flat_nested_object_by_config(value, &NestedConfig::new(root_key, children_key, id_key))
+}
+
+/// 将root_key下的树形数据展开为一维数组，并按配置输出层级、路径等字段
+pub fn flat_nested_object_by_config(value: &Value, config: &NestedConfig) -> anyhow::Result<Value> {
+    let root_key = config.root_key.as_str();
+    let root_value = find_value(root_key, value, true)
+        .map_err(|err| anyhow!("展开嵌套数据失败，未找到root_key: {root_key} {err}"))?;
+    let list = root_value.as_array().ok_or(anyhow!(
+        "展开嵌套数据失败，root_key: {root_key} 对应的数据不是数组"
+    ))?;
 
     let mut data_list = vec![];
-    if let Ok(mut root_value) = root_value {
-        if let Some(list) = root_value.as_array_mut() {
-            for item in list {
-                flat_nested_callback(item, None, &mut data_list, children_key, id_key);
-            }
-        }
+    for (i, item) in list.iter().enumerate() {
+        flat_nested_callback(item, None, i + 1, &mut data_list, config)?;
+    }
 
-        let mut new_value = value.clone();
-        let mut current_key = root_key;
-        let mut current_item = &mut new_value;
-        while let Some(index) = current_key.find('.') {
-            current_item = current_item.get_mut(&current_key[..index]).unwrap();
-            current_key = &current_key[index + 1..];
-        }
+    let mut new_value = value.clone();
+    let mut current_key = root_key;
+    let mut current_item = &mut new_value;
+    while let Some(index) = current_key.find('.') {
+        current_item = current_item
+            .get_mut(&current_key[..index])
+            .ok_or(anyhow!("展开嵌套数据失败，未找到root_key: {root_key}"))?;
+        current_key = &current_key[index + 1..];
+    }
 
-        current_item
-            .as_object_mut()
-            .unwrap()
-            .insert(current_key.to_string(), json!(data_list));
+    current_item
+        .as_object_mut()
+        .ok_or(anyhow!(
+            "展开嵌套数据失败，root_key: {root_key} 的上级不是对象"
+        ))?
+        .insert(current_key.to_string(), json!(data_list));
 
-        return Ok(new_value);
-    }
-    Err(anyhow!("展开嵌套数据失败： {value}"))
+    Ok(new_value)
 }
 
 fn flat_nested_callback(
-    value: &mut Value,
-    parent: Option<&Value>,
+    value: &Value,
+    parent: Option<&NestedParent>,
+    order: usize,
     data_list: &mut Vec<Value>,
-    children_key: &str,
-    id_key: &str,
-) {
+    config: &NestedConfig,
+) -> anyhow::Result<()> {
+    if !value.is_object() {
+        return Err(anyhow!("展开嵌套数据失败，节点不是对象: {value}"));
+    }
+
+    let id_key = config.id_key.as_str();
+    let id = value.get(id_key).unwrap_or(&Value::Null);
+    let id_str = match id.as_str() {
+        Some(x) => x.to_string(),
+        None => id.to_string(),
+    };
+    let separator = config.path_separator.as_deref().unwrap_or("/");
+    let (root_id, path, level) = match parent {
+        None => (id, id_str, 1),
+        Some(x) => (
+            x.root_id,
+            format!("{}{separator}{id_str}", x.path),
+            x.level + 1,
+        ),
+    };
+
+    let mut children_list = vec![];
     let mut new_value = value.clone();
-    new_value.as_object_mut().unwrap().remove(children_key);
-    new_value
+    for children_key in config.children_keys() {
+        if let Some(children) = take_children(&mut new_value, children_key)? {
+            children_list.push(children);
+        }
+    }
+
+    let is_leaf = children_list.iter().all(|x| x.is_empty());
+    let obj = new_value
         .as_object_mut()
-        .unwrap()
-        .entry(format!("parent_{id_key}"))
-        .or_insert_with(|| match parent {
-            None => {
-                json!(null)
-            }
-            Some(x) => x[id_key].clone(),
-        });
+        .ok_or(anyhow!("展开嵌套数据失败，节点不是对象: {value}"))?;
+    let parent_key = match &config.parent_key {
+        None => format!("parent_{id_key}"),
+        Some(x) => x.clone(),
+    };
+    obj.entry(parent_key).or_insert_with(|| match parent {
+        None => json!(null),
+        Some(x) => x.id.clone(),
+    });
+    if let Some(key) = &config.level_key {
+        obj.insert(key.clone(), json!(level));
+    }
+    if let Some(key) = &config.root_id_key {
+        obj.insert(key.clone(), root_id.clone());
+    }
+    if let Some(key) = &config.path_key {
+        obj.insert(key.clone(), json!(path));
+    }
+    if let Some(key) = &config.leaf_key {
+        obj.insert(key.clone(), json!(is_leaf));
+    }
+    if let Some(key) = &config.order_key {
+        obj.insert(key.clone(), json!(order));
+    }
     data_list.push(new_value);
 
-    let children = find_value(children_key, value, true);
-    if let Ok(mut child) = children {
-        if let Some(list) = child.as_array_mut() {
-            for item in list {
-                flat_nested_callback(item, Some(value), data_list, children_key, id_key);
+    let current = NestedParent {
+        id,
+        root_id,
+        path: path.as_str(),
+        level,
+    };
+    for children in children_list {
+        for (i, item) in children.iter().enumerate() {
+            flat_nested_callback(item, Some(&current), i + 1, data_list, config)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 从节点中移除子节点数组并返回，子节点数组移除后中间对象为空时一并移除
+fn take_children(value: &mut Value, children_key: &str) -> anyhow::Result<Option<Vec<Value>>> {
+    let (parent, key) = match children_key.rfind('.') {
+        None => (Some(&mut *value), children_key),
+        Some(index) => {
+            let mut current = Some(&mut *value);
+            for k in children_key[..index].split('.') {
+                current = current.and_then(|x| x.get_mut(k));
             }
+            (current, &children_key[index + 1..])
         }
+    };
+
+    let children = match parent.and_then(|x| x.as_object_mut()) {
+        None => return Ok(None),
+        Some(obj) => obj.remove(key),
+    };
+
+    if let Some(index) = children_key.find('.') {
+        let first_key = &children_key[..index];
+        let is_empty = value
+            .get(first_key)
+            .and_then(|x| x.as_object())
+            .is_some_and(|x| x.is_empty());
+        if is_empty {
+            if let Some(obj) = value.as_object_mut() {
+                obj.remove(first_key);
+            }
+        }
+    }
+
+    match children {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Array(list)) => Ok(Some(list)),
+        Some(x) => Err(anyhow!(
+            "展开嵌套数据失败，子节点字段 {children_key} 不是数组: {x}"
+        )),
     }
 }
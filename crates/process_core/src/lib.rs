@@ -1,4 +1,11 @@
+pub mod auth;
 pub mod db;
+pub mod decode;
 pub mod http;
 pub mod json;
+pub mod mask;
+pub mod pagination;
 pub mod process;
+pub mod rate_limit;
+pub mod step;
+pub mod xml;
@@ -0,0 +1,190 @@
+/// 敏感字段脱敏处理
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+/// 脱敏策略
+/// ```json
+/// {"type": "hash", "salt": "xxx"}
+/// {"type": "partial", "prefix": 3, "suffix": 4}
+/// {"type": "phone"}
+/// {"type": "id_card"}
+/// {"type": "token", "salt": "xxx"}
+/// {"type": "null"}
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MaskPolicy {
+    /// 以salt为密钥的HMAC-SHA256摘要，输出十六进制字符串
+    Hash { salt: Option<String> },
+    /// 保留前prefix位与后suffix位，中间以mask_char替换
+    Partial {
+        prefix: usize,
+        suffix: usize,
+        mask_char: Option<char>,
+    },
+    /// 手机号 138****1234
+    Phone,
+    /// 身份证号 110101********1234
+    IdCard,
+    /// 生成固定长度的令牌，同一个值总是得到同一个令牌，便于关联查询；与hash相同需要salt
+    Token { salt: Option<String> },
+    /// 置空
+    Null,
+}
+
+/// key 与 map_rules 中的路径格式相同，例如：`data#phone`，`result.user.id_card`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MaskRule {
+    pub key: String,
+    pub policy: MaskPolicy,
+}
+
+/// 按调用方身份选择的一组脱敏规则
+/// auth_ids 为空时作为默认规则，对未匹配到的调用方生效
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MaskProfile {
+    pub auth_ids: Option<Vec<String>>,
+    pub rules: Vec<MaskRule>,
+}
+
+impl MaskPolicy {
+    /// 未配置salt时使用default_salt，hash与token最终没有salt时返回错误，
+    /// 没有密钥的摘要可以通过枚举手机号等原值还原
+    pub fn with_default_salt(self, default_salt: &str) -> Result<Self> {
+        let policy = match self {
+            MaskPolicy::Hash { salt: None } => MaskPolicy::Hash {
+                salt: Some(default_salt.to_string()),
+            },
+            MaskPolicy::Token { salt: None } => MaskPolicy::Token {
+                salt: Some(default_salt.to_string()),
+            },
+            x => x,
+        };
+
+        match &policy {
+            MaskPolicy::Hash { salt } | MaskPolicy::Token { salt }
+                if salt.as_deref().unwrap_or_default().is_empty() =>
+            {
+                Err(anyhow!("hash与token脱敏需要配置salt或者环境变量MASK_SALT"))
+            }
+            _ => Ok(policy),
+        }
+    }
+
+    pub fn apply(&self, value: &Value) -> Value {
+        if value.is_null() {
+            return Value::Null;
+        }
+        let origin = match value.as_str() {
+            Some(x) => x.to_string(),
+            None => value.to_string(),
+        };
+
+        match self {
+            MaskPolicy::Hash { salt } => json!(hmac_hex(salt.as_deref(), &origin)),
+            MaskPolicy::Partial {
+                prefix,
+                suffix,
+                mask_char,
+            } => json!(mask_partial(
+                &origin,
+                *prefix,
+                *suffix,
+                mask_char.unwrap_or('*')
+            )),
+            MaskPolicy::Phone => json!(mask_partial(&origin, 3, 4, '*')),
+            MaskPolicy::IdCard => json!(mask_partial(&origin, 6, 4, '*')),
+            MaskPolicy::Token { salt } => {
+                json!(format!("tk_{}", &hmac_hex(salt.as_deref(), &origin)[..16]))
+            }
+            MaskPolicy::Null => Value::Null,
+        }
+    }
+}
+
+fn hmac_hex(salt: Option<&str>, value: &str) -> String {
+    // HMAC可以使用任意长度的密钥，new_from_slice不会失败
+    let mut mac = Hmac::<Sha256>::new_from_slice(salt.unwrap_or_default().as_bytes())
+        .expect("HMAC可以使用任意长度的密钥");
+    mac.update(value.as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|x| format!("{:02x}", x))
+        .collect()
+}
+
+/// 字符数不足prefix + suffix时全部替换
+fn mask_partial(value: &str, prefix: usize, suffix: usize, mask_char: char) -> String {
+    let chars = value.chars().collect::<Vec<char>>();
+    let len = chars.len();
+    if len <= prefix + suffix {
+        return mask_char.to_string().repeat(len);
+    }
+
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            if i < prefix || i >= len - suffix {
+                *c
+            } else {
+                mask_char
+            }
+        })
+        .collect()
+}
+
+/// 对数据中与规则路径匹配的字段进行脱敏
+pub fn mask_data(data: &mut Value, rules: &[MaskRule]) {
+    for rule in rules {
+        mask_by_key(data, rule.key.as_str(), &rule.policy);
+    }
+}
+
+fn mask_by_key(value: &mut Value, key: &str, policy: &MaskPolicy) {
+    if let Some(list) = value.as_array_mut() {
+        for item in list {
+            mask_by_key(item, key, policy);
+        }
+        return;
+    }
+
+    match key.find(['.', '#']) {
+        Some(index) => {
+            if let Some(child) = value.get_mut(&key[..index]) {
+                mask_by_key(child, &key[index + 1..], policy);
+            }
+        }
+        None => {
+            if let Some(target) = value.get_mut(key) {
+                match target.as_array_mut() {
+                    Some(list) => {
+                        for item in list {
+                            *item = policy.apply(item);
+                        }
+                    }
+                    None => {
+                        *target = policy.apply(target);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 根据调用方身份选择脱敏规则，优先匹配auth_ids中包含该身份的配置，其次使用默认配置
+pub fn select_mask_rules<'a>(profiles: &'a [MaskProfile], auth_id: &str) -> Option<&'a [MaskRule]> {
+    profiles
+        .iter()
+        .find(|x| {
+            x.auth_ids
+                .as_ref()
+                .is_some_and(|ids| ids.iter().any(|id| id == auth_id))
+        })
+        .or_else(|| profiles.iter().find(|x| x.auth_ids.is_none()))
+        .map(|x| x.rules.as_slice())
+}
@@ -0,0 +1,302 @@
+/// 分页请求策略：页码、偏移量、游标、Link请求头
+use anyhow::{anyhow, Result};
+use reqwest::header::{HeaderMap, LINK};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::json::find_value;
+
+/// 分页配置
+/// ```json
+/// {"type": "page_number", "page_param": "page", "size_param": "size", "page_size": 100, "total_pages_path": "data.pages", "records_path": "data.list"}
+/// {"type": "offset", "offset_param": "offset", "limit_param": "limit", "limit": 100, "location": "body", "concurrency": 5}
+/// {"type": "cursor", "cursor_param": "next_token", "cursor_path": "meta.next_token"}
+/// {"type": "link_header"}
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PaginationConfig {
+    #[serde(flatten)]
+    pub strategy: PaginationStrategy,
+    /// 分页参数的位置，默认为url的query参数
+    #[serde(default)]
+    pub location: ParamLocation,
+    /// 返回数据中记录数组的路径，数组为空或者不存在时停止分页
+    pub records_path: Option<String>,
+    /// 同时请求的页数，只对page_number与offset生效，第一页请求完成后再并发请求后续的页
+    pub concurrency: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PaginationStrategy {
+    PageNumber {
+        page_param: String,
+        size_param: Option<String>,
+        /// 第一页的页码，默认为1
+        start_page: Option<i64>,
+        page_size: Option<i64>,
+        /// 返回数据中总页数的路径
+        total_pages_path: Option<String>,
+    },
+    Offset {
+        offset_param: String,
+        limit_param: String,
+        limit: i64,
+        /// 返回数据中总条数的路径
+        total_path: Option<String>,
+    },
+    /// 从返回数据中取出下一页的游标，游标为空时停止
+    Cursor {
+        cursor_param: String,
+        cursor_path: String,
+    },
+    /// 按RFC 5988从Link请求头中取出rel="next"的地址
+    LinkHeader,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamLocation {
+    #[default]
+    Query,
+    /// 请求体为JSON对象时写入其中
+    Body,
+}
+
+#[derive(Debug, Clone)]
+pub struct Paginator {
+    config: PaginationConfig,
+    /// 已经请求的页数
+    request_count: i64,
+    cursor: Option<String>,
+    next_url: Option<String>,
+    /// 根据返回数据中的总数计算出的总请求次数
+    total_requests: Option<i64>,
+}
+
+impl Paginator {
+    pub fn new(config: PaginationConfig) -> Self {
+        Self {
+            config,
+            request_count: 0,
+            cursor: None,
+            next_url: None,
+            total_requests: None,
+        }
+    }
+
+    /// 可以同时请求的页数，游标与Link请求头需要依赖上一页的返回，只能依次请求
+    pub fn concurrency(&self) -> usize {
+        match self.config.strategy {
+            PaginationStrategy::PageNumber { .. } | PaginationStrategy::Offset { .. } => {
+                self.config.concurrency.unwrap_or(1).max(1)
+            }
+            _ => 1,
+        }
+    }
+
+    /// 已经请求的页数
+    pub fn request_count(&self) -> i64 {
+        self.request_count
+    }
+
+    /// 剩余的请求次数，返回数据中没有总数时为None
+    pub fn remaining(&self) -> Option<i64> {
+        self.total_requests.map(|x| (x - self.request_count).max(0))
+    }
+
+    /// 从第index（从0开始）页开始请求的分页器，用于并发请求
+    pub fn at_page(&self, index: i64) -> Paginator {
+        Paginator {
+            request_count: index,
+            ..self.clone()
+        }
+    }
+
+    /// 将当前页的分页参数写入url或body
+    pub fn apply(&self, url: &str, body: Option<&str>) -> Result<(String, Option<String>)> {
+        if let Some(next_url) = &self.next_url {
+            return Ok((next_url.clone(), body.map(|x| x.to_string())));
+        }
+
+        let params = match &self.config.strategy {
+            PaginationStrategy::PageNumber {
+                page_param,
+                size_param,
+                start_page,
+                page_size,
+                ..
+            } => {
+                let mut params = vec![(
+                    page_param.clone(),
+                    json!(start_page.unwrap_or(1) + self.request_count),
+                )];
+                if let (Some(size_param), Some(page_size)) = (size_param, page_size) {
+                    params.push((size_param.clone(), json!(page_size)));
+                }
+                params
+            }
+            PaginationStrategy::Offset {
+                offset_param,
+                limit_param,
+                limit,
+                ..
+            } => vec![
+                (offset_param.clone(), json!(self.request_count * limit)),
+                (limit_param.clone(), json!(limit)),
+            ],
+            PaginationStrategy::Cursor { cursor_param, .. } => match &self.cursor {
+                Some(cursor) => vec![(cursor_param.clone(), json!(cursor))],
+                None => vec![],
+            },
+            PaginationStrategy::LinkHeader => vec![],
+        };
+
+        match self.config.location {
+            ParamLocation::Query => {
+                Ok((set_query_params(url, &params)?, body.map(|x| x.to_string())))
+            }
+            ParamLocation::Body => Ok((url.to_string(), Some(set_body_params(body, &params)?))),
+        }
+    }
+
+    /// 根据本页返回的数据与请求头更新分页状态，返回是否还有下一页
+    /// url为本页的请求地址，Link请求头中的相对地址按它解析
+    pub fn advance(&mut self, url: &str, data: &Value, headers: &HeaderMap) -> Result<bool> {
+        self.request_count += 1;
+
+        let records_len = match &self.config.records_path {
+            Some(path) => match find_value(path, data, false) {
+                Ok(Value::Array(list)) if !list.is_empty() => Some(list.len() as i64),
+                _ => return Ok(false),
+            },
+            None => None,
+        };
+
+        match &self.config.strategy {
+            PaginationStrategy::PageNumber {
+                page_size,
+                total_pages_path,
+                ..
+            } => {
+                if let Some(path) = total_pages_path {
+                    let total = find_number(path, data)?;
+                    self.total_requests = Some(total);
+                    return Ok(self.request_count < total);
+                }
+                Ok(!matches!((records_len, page_size), (Some(len), Some(size)) if len < *size))
+            }
+            PaginationStrategy::Offset {
+                limit, total_path, ..
+            } => {
+                if let Some(path) = total_path {
+                    let total = find_number(path, data)?;
+                    self.total_requests = (*limit > 0).then(|| (total + limit - 1) / limit);
+                    return Ok(self.request_count * limit < total);
+                }
+                Ok(!matches!(records_len, Some(len) if len < *limit))
+            }
+            PaginationStrategy::Cursor { cursor_path, .. } => {
+                self.cursor = match find_value(cursor_path, data, false) {
+                    Ok(Value::String(x)) if !x.is_empty() => Some(x),
+                    Ok(Value::Number(x)) => Some(x.to_string()),
+                    _ => None,
+                };
+                Ok(self.cursor.is_some())
+            }
+            PaginationStrategy::LinkHeader => {
+                self.next_url = match headers
+                    .get_all(LINK)
+                    .iter()
+                    .filter_map(|x| x.to_str().ok())
+                    .find_map(find_next_link)
+                {
+                    Some(link) => Some(join_url(url, &link)?),
+                    None => None,
+                };
+                Ok(self.next_url.is_some())
+            }
+        }
+    }
+}
+
+fn find_number(path: &str, data: &Value) -> Result<i64> {
+    let value = find_value(path, data, false).map_err(|_| anyhow!("分页数据中未找到 {path}"))?;
+    match &value {
+        Value::Number(x) => x.as_f64().map(|x| x as i64),
+        Value::String(x) => x.trim().parse::<i64>().ok(),
+        _ => None,
+    }
+    .ok_or(anyhow!("分页数据中 {path} 的值 {value} 不是数字"))
+}
+
+fn set_query_params(url: &str, params: &[(String, Value)]) -> Result<String> {
+    if params.is_empty() {
+        return Ok(url.to_string());
+    }
+    let mut url = Url::parse(url).map_err(|err| anyhow!("url {url} 无法解析: {err}"))?;
+    let pairs = url
+        .query_pairs()
+        .filter(|(key, _)| !params.iter().any(|(k, _)| k == key))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect::<Vec<(String, String)>>();
+
+    {
+        let mut query = url.query_pairs_mut();
+        query.clear().extend_pairs(pairs);
+        for (key, value) in params {
+            match value.as_str() {
+                Some(x) => query.append_pair(key, x),
+                None => query.append_pair(key, &value.to_string()),
+            };
+        }
+    }
+
+    Ok(url.to_string())
+}
+
+fn set_body_params(body: Option<&str>, params: &[(String, Value)]) -> Result<String> {
+    let mut body = match body.map(|x| x.trim()).filter(|x| !x.is_empty()) {
+        Some(x) => serde_json::from_str::<Value>(x)
+            .map_err(|err| anyhow!("分页参数需要写入body，body必须是JSON对象: {err}"))?,
+        None => Value::Object(Map::new()),
+    };
+    let obj = body
+        .as_object_mut()
+        .ok_or(anyhow!("分页参数需要写入body，body必须是JSON对象"))?;
+    for (key, value) in params {
+        obj.insert(key.clone(), value.clone());
+    }
+
+    Ok(body.to_string())
+}
+
+/// Link中的地址可以是相对于请求地址的相对地址
+fn join_url(base: &str, link: &str) -> Result<String> {
+    let base = Url::parse(base).map_err(|err| anyhow!("url {base} 无法解析: {err}"))?;
+    base.join(link)
+        .map(|x| x.to_string())
+        .map_err(|err| anyhow!("Link中的地址 {link} 无法解析: {err}"))
+}
+
+/// 解析 `<https://x/items?page=2>; rel="next", <https://x/items?page=5>; rel="last"`
+fn find_next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let mut parts = link.split(';');
+        let url = parts.next()?.trim();
+        let url = url.strip_prefix('<')?.strip_suffix('>')?;
+        parts
+            .any(|x| {
+                let x = x.trim();
+                x.strip_prefix("rel=")
+                    .map(|rel| {
+                        rel.trim_matches('"')
+                            .split_whitespace()
+                            .any(|r| r == "next")
+                    })
+                    .unwrap_or(false)
+            })
+            .then(|| url.to_string())
+    })
+}
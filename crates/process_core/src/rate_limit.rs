@@ -0,0 +1,118 @@
+/// 令牌桶限流，用于控制对上游接口的请求频率
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// 限流配置
+/// ```json
+/// {"requests": 10, "per": "second"}
+/// {"requests": 600, "per": "minute", "burst": 20}
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RateLimit {
+    pub requests: u32,
+    #[serde(default)]
+    pub per: RatePeriod,
+    /// 允许瞬间发出的最大请求数，默认等于requests
+    pub burst: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RatePeriod {
+    #[default]
+    Second,
+    Minute,
+}
+
+impl RateLimit {
+    /// 每秒生成的令牌数
+    fn rate(&self) -> f64 {
+        let seconds = match self.per {
+            RatePeriod::Second => 1.0,
+            RatePeriod::Minute => 60.0,
+        };
+        self.requests.max(1) as f64 / seconds
+    }
+
+    fn capacity(&self) -> f64 {
+        self.burst.unwrap_or(self.requests).max(1) as f64
+    }
+
+    /// 用于区分限流器的key，配置修改后使用新的限流器
+    pub fn key(&self, name: &str) -> String {
+        format!(
+            "{name}:{}/{:?}/{}",
+            self.requests,
+            self.per,
+            self.burst.unwrap_or(self.requests)
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            bucket: Mutex::new(Bucket {
+                tokens: limit.capacity(),
+                updated_at: Instant::now(),
+            }),
+            limit,
+        }
+    }
+
+    /// 获取一个令牌，令牌不足时等待
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                let now = Instant::now();
+                let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+                bucket.tokens =
+                    (bucket.tokens + elapsed * self.limit.rate()).min(self.limit.capacity());
+                bucket.updated_at = now;
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.limit.rate())
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// 按key共享的限流器，同一个采集配置或者同一个host的请求共用一个令牌桶
+#[derive(Debug, Default)]
+pub struct RateLimiters {
+    limiters: Mutex<HashMap<String, Arc<RateLimiter>>>,
+}
+
+impl RateLimiters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str, limit: &RateLimit) -> Arc<RateLimiter> {
+        self.limiters
+            .lock()
+            .unwrap()
+            .entry(limit.key(name))
+            .or_insert_with(|| Arc::new(RateLimiter::new(limit.clone())))
+            .clone()
+    }
+}
@@ -0,0 +1,155 @@
+/// 多步骤采集：在主请求前后追加请求，后一步可以引用前一步返回的数据
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use futures_util::{stream, StreamExt};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::http::{Http, HttpConfig, ResponseConfig};
+use crate::json::find_value;
+use crate::process::Receive;
+
+/// 采集步骤
+/// ```json
+/// {"name": "token", "stage": "before", "url": "https://x/lookup", "result_path": "data.id"}
+/// {"name": "detail", "url": "https://x/items/${_item.id}", "for_each": "data.list", "concurrency": 5, "result_path": "data"}
+/// ```
+/// before步骤在主请求之前依次执行，结果以步骤名为key保存，主请求与后续步骤中通过`${_steps.token}`引用；
+/// after步骤在主请求之后执行，配置for_each时对数组中的每一项发起请求，通过`${_item.id}`引用当前项，
+/// 结果写入当前项的merge_key中，未配置for_each时结果写入返回数据的merge_key中
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HttpStep {
+    pub name: String,
+    #[serde(default)]
+    pub stage: StepStage,
+    pub url: String,
+    /// 默认为GET
+    pub method: Option<String>,
+    pub headers: Option<HashMap<String, String>>,
+    pub body: Option<String>,
+    /// 需要逐项请求的数组路径，以`.`分隔，为空字符串时表示数据本身就是数组
+    pub for_each: Option<String>,
+    /// for_each时同时发起的请求数，默认为1
+    pub concurrency: Option<usize>,
+    /// 只保留返回数据中该路径的值
+    pub result_path: Option<String>,
+    /// 结果写入的key，默认为步骤名
+    pub merge_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStage {
+    Before,
+    #[default]
+    After,
+}
+
+/// 渲染完成的单次请求
+struct StepRequest {
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+}
+
+impl HttpStep {
+    fn merge_key(&self) -> &str {
+        self.merge_key.as_deref().unwrap_or(&self.name)
+    }
+
+    /// 渲染url、请求头与请求体中的模板
+    fn render<F>(&self, render: &F, item: Option<&Value>) -> Result<StepRequest>
+    where
+        F: Fn(&str, Option<&Value>) -> Result<String>,
+    {
+        let mut headers = vec![];
+        for (key, value) in self.headers.iter().flatten() {
+            headers.push((key.clone(), render(value, item)?));
+        }
+
+        Ok(StepRequest {
+            url: render(&self.url, item)?,
+            headers,
+            body: match &self.body {
+                Some(body) => Some(render(body, item)?),
+                None => None,
+            },
+        })
+    }
+
+    async fn fetch(&self, request: StepRequest, base: &HttpConfig) -> Result<Value> {
+        let method: Method = self.method.as_deref().unwrap_or("GET").parse()?;
+        let config = HttpConfig {
+            method,
+            headers: Some(request.headers),
+            body: request.body,
+            response_config: ResponseConfig::default(),
+            ..base.clone()
+        };
+        debug!("执行采集步骤 {}: {}", self.name, request.url);
+        let mut http = Http::new();
+        let data = http
+            .receive(request.url, config)
+            .await
+            .map_err(|err| anyhow!("采集步骤 {} 请求失败: {err}", self.name))?
+            .data;
+
+        match &self.result_path {
+            Some(path) => find_value(path, &data, false)
+                .map_err(|_| anyhow!("采集步骤 {} 返回的数据中未找到 {path}", self.name)),
+            None => Ok(data),
+        }
+    }
+}
+
+impl Http {
+    /// 执行一个采集步骤并将结果合并到data中
+    /// render用于渲染模板，第二个参数为for_each时的当前项；
+    /// base提供认证、超时重试与返回校验配置，请求方式、请求头与请求体以步骤中的配置为准
+    pub async fn run_step<F>(&mut self, step: &HttpStep, base: &HttpConfig, render: F) -> Result<()>
+    where
+        F: Fn(&str, Option<&Value>) -> Result<String> + Send + Sync,
+    {
+        let merge_key = step.merge_key().to_string();
+        let Some(path) = &step.for_each else {
+            let request = step.render(&render, None)?;
+            let result = step.fetch(request, base).await?;
+            self.data
+                .as_object_mut()
+                .ok_or(anyhow!("采集步骤 {} 的结果只能写入JSON对象", step.name))?
+                .insert(merge_key, result);
+            return Ok(());
+        };
+
+        let pointer = match path.is_empty() {
+            true => String::new(),
+            false => format!("/{}", path.replace('.', "/")),
+        };
+        let items = self
+            .data
+            .pointer_mut(&pointer)
+            .and_then(|x| x.as_array_mut())
+            .ok_or(anyhow!("采集步骤 {} 未找到数组 {path}", step.name))?;
+        let requests = items
+            .iter()
+            .map(|item| step.render(&render, Some(item)))
+            .collect::<Result<Vec<StepRequest>>>()?;
+
+        let results = stream::iter(requests)
+            .map(|request| step.fetch(request, base))
+            .buffered(step.concurrency.unwrap_or(1).max(1))
+            .collect::<Vec<Result<Value>>>()
+            .await;
+
+        for (item, result) in items.iter_mut().zip(results) {
+            item.as_object_mut()
+                .ok_or(anyhow!("采集步骤 {} 的结果只能写入JSON对象", step.name))?
+                .insert(merge_key.clone(), result?);
+        }
+
+        Ok(())
+    }
+}
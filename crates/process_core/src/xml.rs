@@ -0,0 +1,164 @@
+/// 将XML/SOAP格式的数据转换为JSON，以便复用map_rules等处理流程
+use anyhow::{anyhow, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde_json::{json, Map, Value};
+
+/// XML转换为JSON的规则：
+/// 1. 属性以`@`开头，例如：`<a id="1"/>` -> `{"a": {"@id": "1"}}`
+/// 2. 只有文本的元素直接转换为字符串，同时存在属性或子元素时文本保存在`#text`中
+/// 3. 同名的子元素转换为数组
+/// 4. strip_namespace为true时去掉元素与属性名的命名空间前缀，并忽略xmlns声明
+/// ```xml
+/// <root><item id="1">a</item><item id="2">b</item></root>
+/// ```
+/// ```json
+/// {"root": {"item": [{"@id": "1", "#text": "a"}, {"@id": "2", "#text": "b"}]}}
+/// ```
+pub fn xml_to_json(xml: &str, strip_namespace: bool) -> Result<Value> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    // (元素名, 属性与子元素, 文本)
+    let mut stack: Vec<(String, Map<String, Value>, String)> = vec![];
+    let mut root = Map::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let (name, attrs) = parse_element(&e, strip_namespace)?;
+                stack.push((name, attrs, String::new()));
+            }
+            Ok(Event::Empty(e)) => {
+                let (name, attrs) = parse_element(&e, strip_namespace)?;
+                let value = element_value(attrs, String::new());
+                match stack.last_mut() {
+                    Some((_, parent, _)) => insert_child(parent, name, value),
+                    None => insert_child(&mut root, name, value),
+                }
+            }
+            Ok(Event::Text(e)) => {
+                if let Some((_, _, text)) = stack.last_mut() {
+                    text.push_str(&e.unescape()?);
+                }
+            }
+            Ok(Event::CData(e)) => {
+                if let Some((_, _, text)) = stack.last_mut() {
+                    text.push_str(&String::from_utf8_lossy(&e.into_inner()));
+                }
+            }
+            Ok(Event::End(_)) => {
+                let (name, attrs, text) =
+                    stack.pop().ok_or(anyhow!("XML格式错误，缺少开始标签"))?;
+                let value = element_value(attrs, text);
+                match stack.last_mut() {
+                    Some((_, parent, _)) => insert_child(parent, name, value),
+                    None => insert_child(&mut root, name, value),
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(err) => {
+                return Err(anyhow!(
+                    "XML解析失败，位置 {}: {err}",
+                    reader.buffer_position()
+                ))
+            }
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err(anyhow!("XML格式错误，标签未闭合"));
+    }
+    if root.is_empty() {
+        return Err(anyhow!("XML中没有任何元素"));
+    }
+
+    Ok(Value::Object(root))
+}
+
+fn parse_element(e: &BytesStart, strip_namespace: bool) -> Result<(String, Map<String, Value>)> {
+    let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+    let name = match strip_namespace {
+        true => local_name(&name).to_string(),
+        false => name,
+    };
+
+    let mut attrs = Map::new();
+    for attr in e.attributes() {
+        let attr = attr?;
+        let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+        if strip_namespace && (key == "xmlns" || key.starts_with("xmlns:")) {
+            continue;
+        }
+        let key = match strip_namespace {
+            true => local_name(&key).to_string(),
+            false => key,
+        };
+        attrs.insert(format!("@{key}"), json!(attr.unescape_value()?.to_string()));
+    }
+
+    Ok((name, attrs))
+}
+
+fn local_name(name: &str) -> &str {
+    match name.find(':') {
+        Some(index) => &name[index + 1..],
+        None => name,
+    }
+}
+
+fn element_value(mut attrs: Map<String, Value>, text: String) -> Value {
+    if attrs.is_empty() {
+        return match text.is_empty() {
+            true => Value::Null,
+            false => json!(text),
+        };
+    }
+    if !text.is_empty() {
+        attrs.insert("#text".to_string(), json!(text));
+    }
+
+    Value::Object(attrs)
+}
+
+fn insert_child(parent: &mut Map<String, Value>, name: String, value: Value) {
+    match parent.get_mut(&name) {
+        None => {
+            parent.insert(name, value);
+        }
+        Some(Value::Array(list)) => list.push(value),
+        Some(exists) => {
+            let first = exists.take();
+            *exists = json!([first, value]);
+        }
+    }
+}
+
+/// 去掉SOAP信封，返回Body中的内容；Body中为Fault时返回错误
+pub fn unwrap_soap_envelope(value: &Value) -> Result<Value> {
+    let envelope = find_by_local_name(value, "Envelope").ok_or(anyhow!("未找到SOAP Envelope"))?;
+    let body = find_by_local_name(envelope, "Body").ok_or(anyhow!("未找到SOAP Body"))?;
+
+    if let Some(fault) = find_by_local_name(body, "Fault") {
+        let msg = find_by_local_name(fault, "faultstring")
+            .or_else(|| find_by_local_name(fault, "Reason"))
+            .unwrap_or(fault);
+        return Err(anyhow!("SOAP请求返回错误: {msg}"));
+    }
+
+    let mut body = body.clone();
+    if let Some(obj) = body.as_object_mut() {
+        obj.retain(|key, _| !key.starts_with('@'));
+    }
+
+    Ok(body)
+}
+
+fn find_by_local_name<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    value
+        .as_object()?
+        .iter()
+        .find(|(key, _)| local_name(key) == name)
+        .map(|(_, x)| x)
+}
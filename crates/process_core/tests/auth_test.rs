@@ -0,0 +1,89 @@
+use anyhow::Result;
+use process_core::auth::{AuthConfig, AuthProvider};
+use process_core::http::ConnectionConfig;
+use reqwest::Method;
+
+#[actix_rt::test]
+async fn test_basic_auth() -> Result<()> {
+    let provider = AuthProvider::new(AuthConfig::Basic {
+        username: "user".to_string(),
+        password: "pass".to_string(),
+    });
+    let headers = provider
+        .headers(
+            &Method::GET,
+            "http://localhost/api",
+            None,
+            &ConnectionConfig::default(),
+        )
+        .await?;
+
+    assert_eq!(
+        headers,
+        vec![(
+            "Authorization".to_string(),
+            "Basic dXNlcjpwYXNz".to_string()
+        )]
+    );
+
+    Ok(())
+}
+
+#[actix_rt::test]
+async fn test_hmac_auth() -> Result<()> {
+    let config: AuthConfig = serde_json::from_str(
+        r#"{"type": "hmac", "key_id": "id", "secret": "secret", "signature_header": "X-Sign"}"#,
+    )?;
+    let provider = AuthProvider::new(config);
+    let headers = provider
+        .headers(
+            &Method::POST,
+            "http://localhost/api",
+            Some("{}"),
+            &ConnectionConfig::default(),
+        )
+        .await?;
+    let keys = headers.iter().map(|x| x.0.as_str()).collect::<Vec<&str>>();
+
+    assert_eq!(keys, vec!["X-Key-Id", "X-Sign", "X-Timestamp", "X-Nonce"]);
+    assert_eq!(headers[0].1, "id");
+    assert!(!headers[1].1.is_empty());
+
+    // 每次请求的nonce不同，签名也不同
+    let headers2 = provider
+        .headers(
+            &Method::POST,
+            "http://localhost/api",
+            Some("{}"),
+            &ConnectionConfig::default(),
+        )
+        .await?;
+    assert_ne!(headers[3].1, headers2[3].1);
+    assert_ne!(headers[1].1, headers2[1].1);
+    assert!(!format!("{:?}", provider).contains("secret"));
+
+    Ok(())
+}
+
+#[actix_rt::test]
+async fn test_token_connection() -> Result<()> {
+    let provider = AuthProvider::new(AuthConfig::OAuth2ClientCredentials {
+        token_url: "http://localhost/token".to_string(),
+        client_id: "id".to_string(),
+        client_secret: "secret".to_string(),
+        scope: None,
+    });
+    let connection = ConnectionConfig {
+        proxy: Some("://".to_string()),
+        ..Default::default()
+    };
+
+    // 获取token的请求同样使用连接配置中的代理
+    let err = provider
+        .headers(&Method::GET, "http://localhost/api", None, &connection)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("代理地址无法解析"));
+
+    Ok(())
+}
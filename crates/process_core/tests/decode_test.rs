@@ -0,0 +1,88 @@
+use process_core::decode::RecordDecoder;
+use process_core::http::{decode_response, ResponseConfig, ResponseFormat};
+use serde_json::json;
+
+fn config(format: ResponseFormat) -> ResponseConfig {
+    ResponseConfig {
+        format,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn ndjson_chunk_test() {
+    let mut decoder = RecordDecoder::new(&config(ResponseFormat::Ndjson))
+        .unwrap()
+        .unwrap();
+    // 记录在分块中间被截断
+    decoder.push(br#"{"id": 1}"#).unwrap();
+    decoder.push(b"\r\n{\"id\"").unwrap();
+    decoder.push(b": 2}\n\n").unwrap();
+
+    assert_eq!(
+        decoder.finish().unwrap(),
+        vec![json!({"id": 1}), json!({"id": 2})]
+    );
+
+    assert!(decode_response("{\"id\": 1}\n{id}", None, &config(ResponseFormat::Ndjson)).is_err());
+}
+
+#[test]
+fn csv_test() {
+    let mut decoder = RecordDecoder::new(&config(ResponseFormat::Csv))
+        .unwrap()
+        .unwrap();
+    decoder
+        .push("\u{feff}id,name,desc\n1,a,\"x, \"\"y\"\"".as_bytes())
+        .unwrap();
+    decoder.push(b"\nz\"\n2,b").unwrap();
+
+    assert_eq!(
+        decoder.finish().unwrap(),
+        vec![
+            json!({"id": "1", "name": "a", "desc": "x, \"y\"\nz"}),
+            json!({"id": "2", "name": "b", "desc": null}),
+        ]
+    );
+
+    assert_eq!(
+        decode_response("a\tb\n1\t2\n", None, &config(ResponseFormat::Tsv)).unwrap(),
+        json!([{"a": "1", "b": "2"}])
+    );
+}
+
+#[test]
+fn lines_test() {
+    let regex_config = ResponseConfig {
+        format: ResponseFormat::Lines,
+        line_regex: Some(r"^(?P<level>\w+): (?P<msg>.*)$".to_string()),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        decode_response("INFO: start\n--\nWARN: stop", None, &regex_config).unwrap(),
+        json!([
+            {"level": "INFO", "msg": "start"},
+            {"level": "WARN", "msg": "stop"}
+        ])
+    );
+
+    assert_eq!(
+        decode_response("a\nb", None, &config(ResponseFormat::Lines)).unwrap(),
+        json!([{"line": "a"}, {"line": "b"}])
+    );
+}
+
+#[test]
+fn csv_delimiter_test() {
+    let config = |delimiter| ResponseConfig {
+        format: ResponseFormat::Csv,
+        delimiter: Some(delimiter),
+        ..Default::default()
+    };
+
+    assert!(RecordDecoder::new(&config(';')).is_ok());
+    assert!(RecordDecoder::new(&config('，')).is_err());
+    assert!(RecordDecoder::new(&config('"')).is_err());
+    assert!(decode_response("a，b\n1，2", None, &config('，')).is_err());
+}
@@ -1,4 +1,7 @@
-use process_core::json::{find_value, flat_nested_object, map_data};
+use process_core::json::{
+    check_map_rules, find_value, flat_nested_object, flat_nested_object_by_config, map_data,
+    NestedConfig,
+};
 use serde_json::json;
 
 #[test]
@@ -260,3 +263,85 @@ fn test_flat_nested_object() {
         })
     )
 }
+
+#[test]
+fn test_flat_nested_object_by_config() {
+    let origin_data = json!({
+        "data": [
+            {
+                "id": 1,
+                "name": "省",
+                "sub": {
+                    "items": [
+                        {"id": 4, "name": "市1", "towns": [{"id": 7, "name": "县"}]},
+                        {"id": 5, "name": "市2"}
+                    ]
+                },
+                "towns": []
+            }
+        ]
+    });
+
+    let config: NestedConfig = serde_json::from_value(json!({
+        "root_key": "data",
+        "children_key": "sub.items",
+        "extra_children_keys": ["towns"],
+        "id_key": "id",
+        "parent_key": "pid",
+        "level_key": "level",
+        "root_id_key": "root_id",
+        "path_key": "path",
+        "leaf_key": "is_leaf",
+        "order_key": "sort"
+    }))
+    .unwrap();
+
+    let new_data = flat_nested_object_by_config(&origin_data, &config).expect("err");
+
+    assert_eq!(
+        new_data,
+        json!({
+            "data": [
+                {"id": 1, "name": "省", "pid": null, "level": 1, "root_id": 1, "path": "1", "is_leaf": false, "sort": 1},
+                {"id": 4, "name": "市1", "pid": 1, "level": 2, "root_id": 1, "path": "1/4", "is_leaf": false, "sort": 1},
+                {"id": 7, "name": "县", "pid": 4, "level": 3, "root_id": 1, "path": "1/4/7", "is_leaf": true, "sort": 1},
+                {"id": 5, "name": "市2", "pid": 1, "level": 2, "root_id": 1, "path": "1/5", "is_leaf": true, "sort": 2}
+            ]
+        })
+    );
+}
+
+#[test]
+fn test_flat_nested_object_err() {
+    assert!(flat_nested_object(&json!({"data": [1, 2]}), "data", "children", "id").is_err());
+    assert!(flat_nested_object(&json!({"result": {}}), "result.data", "children", "id").is_err());
+    assert!(flat_nested_object(
+        &json!({"data": [{"id": 1, "children": {"id": 2}}]}),
+        "data",
+        "children",
+        "id"
+    )
+    .is_err());
+}
+
+#[test]
+fn test_check_map_rules() {
+    let data = json!({"data": [{"id": 1, "name": null}, {"id": 2, "name": null}], "total": 2});
+    let rules = vec![
+        ["data#id".to_string(), "list#id".to_string()],
+        ["data#name".to_string(), "list#name".to_string()],
+        ["data#code".to_string(), "list#code".to_string()],
+        ["total".to_string(), "total".to_string()],
+        ["rows#id".to_string(), "list#id".to_string()],
+    ];
+
+    let res = check_map_rules(&data, &rules);
+    assert_eq!(res[0].matched, 2);
+    assert_eq!(res[0].message, None);
+    assert_eq!(res[1].matched, 0);
+    assert!(res[1].message.as_ref().unwrap().contains("都为空"));
+    // 字段不存在与字段的值都为null需要区分
+    assert!(res[2].message.as_ref().unwrap().contains("未匹配到数据"));
+    assert!(res[3].message.as_ref().unwrap().contains("不是数组"));
+    assert!(res[4].message.as_ref().unwrap().contains("未匹配到数据"));
+}
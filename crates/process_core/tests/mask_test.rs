@@ -0,0 +1,102 @@
+use process_core::mask::{mask_data, select_mask_rules, MaskPolicy, MaskProfile, MaskRule};
+use serde_json::json;
+
+#[test]
+fn mask_policy_test() {
+    assert_eq!(
+        MaskPolicy::Phone.apply(&json!("13812341234")),
+        json!("138****1234")
+    );
+    assert_eq!(
+        MaskPolicy::IdCard.apply(&json!("110101199001011234")),
+        json!("110101********1234")
+    );
+    assert_eq!(
+        MaskPolicy::Partial {
+            prefix: 1,
+            suffix: 0,
+            mask_char: Some('#')
+        }
+        .apply(&json!("张三丰")),
+        json!("张##")
+    );
+    assert_eq!(MaskPolicy::Null.apply(&json!("a")), json!(null));
+
+    let hash1 = MaskPolicy::Hash {
+        salt: Some("s1".to_string()),
+    }
+    .apply(&json!(123));
+    let hash2 = MaskPolicy::Hash {
+        salt: Some("s2".to_string()),
+    }
+    .apply(&json!("123"));
+    assert_eq!(hash1.as_str().unwrap().len(), 64);
+    assert_ne!(hash1, hash2);
+
+    let policy = MaskPolicy::Token { salt: None }
+        .with_default_salt("s1")
+        .unwrap();
+    let token = policy.apply(&json!("abc"));
+    assert_eq!(token, policy.apply(&json!("abc")));
+    assert!(token.as_str().unwrap().starts_with("tk_"));
+
+    // 没有salt的hash与token不允许使用
+    assert!(MaskPolicy::Token { salt: None }
+        .with_default_salt("")
+        .is_err());
+    assert!(MaskPolicy::Hash {
+        salt: Some("".to_string())
+    }
+    .with_default_salt("")
+    .is_err());
+    assert_eq!(
+        MaskPolicy::Phone.with_default_salt("").unwrap(),
+        MaskPolicy::Phone
+    );
+}
+
+#[test]
+fn mask_data_test() {
+    let mut data = json!({
+        "res": {
+            "data": [
+                {"name": "a", "phone": "13812341234"},
+                {"name": "b", "phone": null}
+            ]
+        }
+    });
+    mask_data(
+        &mut data,
+        &[MaskRule {
+            key: "res.data#phone".to_string(),
+            policy: MaskPolicy::Phone,
+        }],
+    );
+
+    assert_eq!(
+        data,
+        json!({
+            "res": {
+                "data": [
+                    {"name": "a", "phone": "138****1234"},
+                    {"name": "b", "phone": null}
+                ]
+            }
+        })
+    );
+}
+
+#[test]
+fn select_mask_rules_test() {
+    let profiles: Vec<MaskProfile> = serde_json::from_value(json!([
+        {"auth_ids": null, "rules": [{"key": "phone", "policy": {"type": "null"}}]},
+        {"auth_ids": ["trusted"], "rules": [{"key": "phone", "policy": {"type": "phone"}}]}
+    ]))
+    .unwrap();
+
+    let rules = select_mask_rules(&profiles, "trusted").unwrap();
+    assert_eq!(rules[0].policy, MaskPolicy::Phone);
+
+    let rules = select_mask_rules(&profiles, "other").unwrap();
+    assert_eq!(rules[0].policy, MaskPolicy::Null);
+}
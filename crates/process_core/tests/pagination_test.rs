@@ -0,0 +1,159 @@
+use anyhow::Result;
+use process_core::pagination::{PaginationConfig, Paginator};
+use reqwest::header::{HeaderMap, HeaderValue, LINK};
+use serde_json::json;
+
+const URL: &str = "http://localhost/api";
+
+#[test]
+fn test_page_number_query() -> Result<()> {
+    let config: PaginationConfig = serde_json::from_value(json!({
+        "type": "page_number",
+        "page_param": "page",
+        "size_param": "size",
+        "page_size": 2,
+        "total_pages_path": "data.pages"
+    }))?;
+    let mut paginator = Paginator::new(config);
+    let headers = HeaderMap::new();
+
+    let (url, body) = paginator.apply("http://localhost/api?page=9&a=1", None)?;
+    assert_eq!(url, "http://localhost/api?a=1&page=1&size=2");
+    assert_eq!(body, None);
+
+    assert!(paginator.advance(URL, &json!({"data": {"pages": 2}}), &headers)?);
+    let (url, _) = paginator.apply("http://localhost/api?a=1", None)?;
+    assert_eq!(url, "http://localhost/api?a=1&page=2&size=2");
+    assert!(!paginator.advance(URL, &json!({"data": {"pages": "2"}}), &headers)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_offset_body() -> Result<()> {
+    let config: PaginationConfig = serde_json::from_value(json!({
+        "type": "offset",
+        "offset_param": "offset",
+        "limit_param": "limit",
+        "limit": 2,
+        "location": "body",
+        "records_path": "list"
+    }))?;
+    let mut paginator = Paginator::new(config);
+    let headers = HeaderMap::new();
+
+    let (_, body) = paginator.apply("http://localhost/api", Some(r#"{"a": 1}"#))?;
+    assert_eq!(body, Some(r#"{"a":1,"limit":2,"offset":0}"#.to_string()));
+
+    assert!(paginator.advance(URL, &json!({"list": [1, 2]}), &headers)?);
+    let (_, body) = paginator.apply("http://localhost/api", None)?;
+    assert_eq!(body, Some(r#"{"limit":2,"offset":2}"#.to_string()));
+
+    // 返回的记录数小于limit时停止
+    assert!(!paginator.advance(URL, &json!({"list": [3]}), &headers)?);
+
+    let mut paginator = Paginator::new(serde_json::from_value(json!({
+        "type": "offset", "offset_param": "o", "limit_param": "l", "limit": 2, "records_path": "list"
+    }))?);
+    assert!(!paginator.advance(URL, &json!({"list": []}), &headers)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_cursor() -> Result<()> {
+    let config: PaginationConfig = serde_json::from_value(json!({
+        "type": "cursor",
+        "cursor_param": "next_token",
+        "cursor_path": "meta.next"
+    }))?;
+    let mut paginator = Paginator::new(config);
+    let headers = HeaderMap::new();
+
+    let (url, _) = paginator.apply("http://localhost/api", None)?;
+    assert_eq!(url, "http://localhost/api");
+
+    assert!(paginator.advance(URL, &json!({"meta": {"next": "abc"}}), &headers)?);
+    let (url, _) = paginator.apply("http://localhost/api", None)?;
+    assert_eq!(url, "http://localhost/api?next_token=abc");
+
+    assert!(!paginator.advance(URL, &json!({"meta": {"next": null}}), &headers)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_link_header() -> Result<()> {
+    let config: PaginationConfig = serde_json::from_value(json!({"type": "link_header"}))?;
+    let mut paginator = Paginator::new(config);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        LINK,
+        HeaderValue::from_static(
+            r#"<http://localhost/api?page=1>; rel="prev", <http://localhost/api?page=3>; rel="next""#,
+        ),
+    );
+    assert!(paginator.advance(URL, &json!([]), &headers)?);
+    let (url, _) = paginator.apply("http://localhost/api", None)?;
+    assert_eq!(url, "http://localhost/api?page=3");
+
+    assert!(!paginator.advance(URL, &json!([]), &HeaderMap::new())?);
+
+    Ok(())
+}
+
+#[test]
+fn test_relative_link_header() -> Result<()> {
+    let config: PaginationConfig = serde_json::from_value(json!({"type": "link_header"}))?;
+    let mut paginator = Paginator::new(config);
+    let link = |value: &'static str| {
+        let mut headers = HeaderMap::new();
+        headers.insert(LINK, HeaderValue::from_static(value));
+        headers
+    };
+
+    let first = "http://localhost/v1/items?page=1";
+    assert!(paginator.advance(
+        first,
+        &json!([]),
+        &link(r#"</api/items?page=2>; rel="next""#)
+    )?);
+    let (url, _) = paginator.apply(first, None)?;
+    assert_eq!(url, "http://localhost/api/items?page=2");
+
+    // 之后的相对地址按上一页的地址解析
+    assert!(paginator.advance(&url, &json!([]), &link(r#"<?page=3>; rel="next""#))?);
+    let (url, _) = paginator.apply(first, None)?;
+    assert_eq!(url, "http://localhost/api/items?page=3");
+
+    Ok(())
+}
+
+#[test]
+fn test_concurrent_pages() -> Result<()> {
+    let mut paginator = Paginator::new(serde_json::from_value(json!({
+        "type": "offset", "offset_param": "o", "limit_param": "l", "limit": 10,
+        "total_path": "total", "concurrency": 4
+    }))?);
+    let headers = HeaderMap::new();
+    assert_eq!(paginator.concurrency(), 4);
+    assert_eq!(paginator.remaining(), None);
+
+    assert!(paginator.advance(URL, &json!({"total": 35}), &headers)?);
+    assert_eq!(paginator.remaining(), Some(3));
+
+    let mut page = paginator.at_page(3);
+    let (url, _) = page.apply("http://localhost/api", None)?;
+    assert_eq!(url, "http://localhost/api?o=30&l=10");
+    assert!(!page.advance(URL, &json!({"total": 35}), &headers)?);
+    assert_eq!(page.request_count(), 4);
+
+    // 游标分页只能依次请求
+    let paginator = Paginator::new(serde_json::from_value(json!({
+        "type": "cursor", "cursor_param": "c", "cursor_path": "next", "concurrency": 4
+    }))?);
+    assert_eq!(paginator.concurrency(), 1);
+
+    Ok(())
+}
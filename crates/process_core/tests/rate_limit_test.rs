@@ -0,0 +1,32 @@
+use std::time::{Duration, Instant};
+
+use process_core::rate_limit::{RateLimit, RateLimiters};
+use serde_json::json;
+
+#[actix_rt::test]
+async fn test_rate_limiter() -> anyhow::Result<()> {
+    let limit: RateLimit = serde_json::from_value(json!({"requests": 20, "burst": 2}))?;
+    let limiters = RateLimiters::new();
+    let limiter = limiters.get("collect_config:1", &limit);
+
+    let start = Instant::now();
+    for _ in 0..6 {
+        limiter.acquire().await;
+    }
+    // 前2个请求使用初始令牌，后4个每个需要等待50ms
+    assert!(start.elapsed() >= Duration::from_millis(190));
+    assert!(start.elapsed() < Duration::from_millis(1000));
+
+    // 相同的名称与配置共用限流器，配置修改后使用新的限流器
+    assert!(std::sync::Arc::ptr_eq(
+        &limiter,
+        &limiters.get("collect_config:1", &limit)
+    ));
+    let limit: RateLimit = serde_json::from_value(json!({"requests": 1, "per": "minute"}))?;
+    assert!(!std::sync::Arc::ptr_eq(
+        &limiter,
+        &limiters.get("collect_config:1", &limit)
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use process_core::db::{checksum_rows, parse_count, Database};
+use serde_json::json;
+
+#[test]
+fn test_limit_sql() {
+    let sql = "select * from users order by id;";
+    assert_eq!(
+        Database::POSTGRES.limit_sql(sql, 10),
+        "SELECT * FROM (select * from users order by id) t LIMIT 10"
+    );
+    assert_eq!(
+        Database::MSSQL.limit_sql(sql, 10),
+        "select * from users order by id OFFSET 0 ROWS FETCH NEXT 10 ROWS ONLY"
+    );
+    assert_eq!(
+        Database::ORACLE.limit_sql(sql, 10),
+        "SELECT * FROM (select * from users order by id) WHERE ROWNUM <= 10"
+    );
+    assert_eq!(
+        Database::POSTGRES.count_sql(sql),
+        "SELECT COUNT(*) AS total FROM (select * from users order by id) t"
+    );
+}
+
+#[test]
+fn test_mssql_ordered_sql() {
+    let sql = "select id, (select max(x) from t2 order by x) m from users Order  By id desc";
+    assert_eq!(
+        Database::MSSQL.count_sql(sql),
+        "SELECT COUNT(*) AS total FROM (select id, (select max(x) from t2 order by x) m from users) t"
+    );
+    assert_eq!(
+        Database::MSSQL.select_columns_sql(sql, &["id".to_string()]),
+        "SELECT id FROM (select id, (select max(x) from t2 order by x) m from users) t"
+    );
+
+    // 没有ORDER BY，或者ORDER BY与TOP、OFFSET一起使用时不需要处理
+    let sql = "select * from users where remark = 'order by'";
+    assert_eq!(
+        Database::MSSQL.limit_sql(sql, 5),
+        format!("SELECT TOP 5 * FROM ({sql}) t")
+    );
+    let sql = "select top 100 * from users order by id";
+    assert_eq!(
+        Database::MSSQL.count_sql(sql),
+        format!("SELECT COUNT(*) AS total FROM ({sql}) t")
+    );
+    let sql = "select * from users order by id offset 10 rows";
+    assert_eq!(
+        Database::MSSQL.count_sql(sql),
+        format!("SELECT COUNT(*) AS total FROM ({sql}) t")
+    );
+}
+
+#[test]
+fn test_parse_count() {
+    assert_eq!(parse_count(&[json!({"total": 3})]).unwrap(), 3);
+    assert_eq!(parse_count(&[json!({"TOTAL": "12"})]).unwrap(), 12);
+    assert!(parse_count(&[]).is_err());
+}
+
+#[test]
+fn test_checksum_rows() {
+    let columns = vec!["id".to_string(), "name".to_string()];
+    let source = [
+        json!({"id": 1, "name": "a", "age": 10}),
+        json!({"id": 2, "name": null}),
+    ];
+    let target = [
+        json!({"NAME": "", "ID": "2"}),
+        json!({"NAME": "a", "ID": "1"}),
+    ];
+
+    assert_eq!(
+        checksum_rows(&source, &columns).unwrap(),
+        checksum_rows(&target, &columns).unwrap()
+    );
+
+    let partial = [json!({"id": 1, "name": "a"})];
+    assert_ne!(
+        checksum_rows(&source, &columns).unwrap(),
+        checksum_rows(&partial, &columns).unwrap()
+    );
+    assert!(checksum_rows(&source, &["code".to_string()]).is_err());
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use process_core::http::{Http, HttpConfig};
+use process_core::step::{HttpStep, StepStage};
+use serde_json::{json, Value};
+
+#[actix_rt::test]
+async fn test_http_step() -> Result<()> {
+    let step: HttpStep = serde_json::from_value(json!({
+        "name": "detail",
+        "url": "http://127.0.0.1:1/items/${_item.id}",
+        "for_each": "data.list",
+        "concurrency": 5
+    }))?;
+    assert_eq!(step.stage, StepStage::After);
+    assert_eq!(step.method, None);
+
+    let render = |template: &str, _item: Option<&Value>| Ok(template.to_string());
+
+    // 数组为空时不发起请求
+    let mut http = Http::new();
+    http.set_data(json!({"data": {"list": []}}));
+    http.run_step(&step, &HttpConfig::default(), render).await?;
+    assert_eq!(http.data, json!({"data": {"list": []}}));
+
+    // 未找到for_each对应的数组
+    http.set_data(json!({"data": {"items": []}}));
+    assert!(http
+        .run_step(&step, &HttpConfig::default(), render)
+        .await
+        .is_err());
+
+    // 渲染失败时不发起请求
+    http.set_data(json!({"data": {"list": [{"id": 1}]}}));
+    let res = http
+        .run_step(&step, &HttpConfig::default(), |_, _| {
+            Err(anyhow::anyhow!("render error"))
+        })
+        .await;
+    assert_eq!(res.unwrap_err().to_string(), "render error");
+
+    Ok(())
+}
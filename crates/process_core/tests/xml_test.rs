@@ -0,0 +1,82 @@
+use process_core::http::{decode_response, ResponseConfig, ResponseFormat};
+use process_core::xml::{unwrap_soap_envelope, xml_to_json};
+use serde_json::json;
+
+#[test]
+fn xml_to_json_test() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <root xmlns:a="http://a">
+            <code>SUCCESS</code>
+            <a:item id="1">name1</a:item>
+            <a:item id="2"><![CDATA[name<2>]]></a:item>
+            <empty/>
+        </root>"#;
+
+    assert_eq!(
+        xml_to_json(xml, false).expect("err"),
+        json!({
+            "root": {
+                "@xmlns:a": "http://a",
+                "code": "SUCCESS",
+                "a:item": [
+                    {"@id": "1", "#text": "name1"},
+                    {"@id": "2", "#text": "name<2>"}
+                ],
+                "empty": null
+            }
+        })
+    );
+
+    assert_eq!(
+        xml_to_json(xml, true).expect("err")["root"]["item"][1]["#text"],
+        json!("name<2>")
+    );
+
+    assert!(xml_to_json("<root><a></root>", false).is_err());
+}
+
+#[test]
+fn soap_envelope_test() {
+    let xml = r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+            <soap:Body>
+                <GetDataResponse xmlns="http://tempuri.org/">
+                    <GetDataResult><Row><Id>1</Id></Row><Row><Id>2</Id></Row></GetDataResult>
+                </GetDataResponse>
+            </soap:Body>
+        </soap:Envelope>"#;
+    let value = xml_to_json(xml, true).expect("err");
+
+    assert_eq!(
+        unwrap_soap_envelope(&value).expect("err"),
+        json!({
+            "GetDataResponse": {
+                "GetDataResult": {"Row": [{"Id": "1"}, {"Id": "2"}]}
+            }
+        })
+    );
+
+    let fault = r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+            <soap:Body><soap:Fault><faultstring>bad request</faultstring></soap:Fault></soap:Body>
+        </soap:Envelope>"#;
+    let value = xml_to_json(fault, false).expect("err");
+    assert!(unwrap_soap_envelope(&value).is_err());
+}
+
+#[test]
+fn decode_response_test() {
+    let config = ResponseConfig::default();
+    assert_eq!(
+        decode_response(r#"{"a": 1}"#, Some("application/json"), &config).expect("err"),
+        json!({"a": 1})
+    );
+    assert_eq!(
+        decode_response("<a>1</a>", None, &config).expect("err"),
+        json!({"a": "1"})
+    );
+
+    let config = ResponseConfig {
+        format: ResponseFormat::Json,
+        ..Default::default()
+    };
+    assert!(decode_response("<a>1</a>", None, &config).is_err());
+}
@@ -17,6 +17,13 @@ pub trait JDBC {
     fn prepare_statement(&mut self, sql_str: &str) -> Result<&Self::Connection>;
 
     fn close(&mut self) -> Result<()>;
+
+    /// 关闭自动提交后需要手动调用commit或rollback
+    fn set_auto_commit(&mut self, auto_commit: bool) -> Result<()>;
+
+    fn commit(&mut self) -> Result<()>;
+
+    fn rollback(&mut self) -> Result<()>;
 }
 
 pub trait ExecuteJDBC {
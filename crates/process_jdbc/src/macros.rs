@@ -56,6 +56,30 @@ macro_rules! impl_jdbc {
 
                 Ok(())
             }
+
+            fn set_auto_commit(&mut self, auto_commit: bool) -> Result<()> {
+                self.jvm.invoke(
+                    self.conn.as_ref().unwrap(),
+                    "setAutoCommit",
+                    &[InvocationArg::try_from(auto_commit)?.into_primitive()?],
+                )?;
+
+                Ok(())
+            }
+
+            fn commit(&mut self) -> Result<()> {
+                self.jvm
+                    .invoke(self.conn.as_ref().unwrap(), "commit", &Vec::new())?;
+
+                Ok(())
+            }
+
+            fn rollback(&mut self) -> Result<()> {
+                self.jvm
+                    .invoke(self.conn.as_ref().unwrap(), "rollback", &Vec::new())?;
+
+                Ok(())
+            }
         }
     };
 }
@@ -11,6 +11,25 @@ mod m20240319_061549_update_data_sharing_config_table;
 mod m20240327_063820_update_sharing_request_log;
 mod m20240402_033637_update_collect_config_table;
 mod m20240408_033448_update_collect_log_table;
+mod m20240412_031522_add_mask_rules_column;
+mod m20240415_063012_update_collect_config_response_config;
+mod m20240418_021530_create_auth_profile_table;
+mod m20240422_015344_update_collect_config_http_options;
+mod m20240424_072106_update_collect_config_response_assertion;
+mod m20240426_031207_update_collect_config_pagination_config;
+mod m20240429_062514_update_collect_config_steps;
+mod m20240506_083342_add_connection_config_column;
+mod m20240509_021847_create_http_capture_table;
+mod m20240513_031406_add_sync_reconcile_columns;
+mod m20240516_020318_update_sync_log_table;
+mod m20240520_064215_add_task_priority_columns;
+mod m20240524_071903_create_task_dependency_table;
+mod m20240528_023614_create_schedule_state_table;
+mod m20240531_062247_add_cron_timezone_column;
+mod m20240604_031158_add_config_enabled_column;
+mod m20240607_014325_add_task_retry_columns;
+mod m20240611_025836_create_run_event_table;
+mod m20240612_021547_reset_schedule_fire_time;
 
 pub struct Migrator;
 
@@ -29,6 +48,25 @@ impl MigratorTrait for Migrator {
             Box::new(m20240327_063820_update_sharing_request_log::Migration),
             Box::new(m20240402_033637_update_collect_config_table::Migration),
             Box::new(m20240408_033448_update_collect_log_table::Migration),
+            Box::new(m20240412_031522_add_mask_rules_column::Migration),
+            Box::new(m20240415_063012_update_collect_config_response_config::Migration),
+            Box::new(m20240418_021530_create_auth_profile_table::Migration),
+            Box::new(m20240422_015344_update_collect_config_http_options::Migration),
+            Box::new(m20240424_072106_update_collect_config_response_assertion::Migration),
+            Box::new(m20240426_031207_update_collect_config_pagination_config::Migration),
+            Box::new(m20240429_062514_update_collect_config_steps::Migration),
+            Box::new(m20240506_083342_add_connection_config_column::Migration),
+            Box::new(m20240509_021847_create_http_capture_table::Migration),
+            Box::new(m20240513_031406_add_sync_reconcile_columns::Migration),
+            Box::new(m20240516_020318_update_sync_log_table::Migration),
+            Box::new(m20240520_064215_add_task_priority_columns::Migration),
+            Box::new(m20240524_071903_create_task_dependency_table::Migration),
+            Box::new(m20240528_023614_create_schedule_state_table::Migration),
+            Box::new(m20240531_062247_add_cron_timezone_column::Migration),
+            Box::new(m20240604_031158_add_config_enabled_column::Migration),
+            Box::new(m20240607_014325_add_task_retry_columns::Migration),
+            Box::new(m20240611_025836_create_run_event_table::Migration),
+            Box::new(m20240612_021547_reset_schedule_fire_time::Migration),
         ]
     }
 }
@@ -165,6 +165,21 @@ pub enum CollectConfig {
     MaxCountOfRequest,
     DbColumnsConfig,
     DbColumnsConfig2,
+    MaskRules,
+    ResponseConfig,
+    AuthProfileId,
+    HttpOptions,
+    ResponseAssertion,
+    PaginationConfig,
+    Steps,
+    ConnectionConfig,
+    CaptureConfig,
+    Priority,
+    OverlapPolicy,
+    MisfirePolicy,
+    Timezone,
+    Enabled,
+    RetryConfig,
     Cron,
     DelFlag,
     JobId,
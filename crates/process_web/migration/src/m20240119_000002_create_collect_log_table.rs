@@ -78,6 +78,21 @@ pub enum CollectLog {
     RunningLog,
     CollectConfigId,
     TaskId,
+    RunId,
+    NodeId,
+    Attempt,
+    MaxAttempts,
+    RetryOf,
+    Partial,
+    StartedAt,
+    FinishedAt,
+    DurationMs,
+    RequestCount,
+    RowsFetched,
+    RowsWritten,
+    RowsRejected,
+    Bytes,
+    ErrorClass,
     Status,
     UpdateTime,
     CreateTime,
@@ -113,6 +113,13 @@ pub enum SyncConfig {
     TargetQuerySqlTemplate,
     Cron,
     JobId,
+    ReconcileConfig,
+    Priority,
+    OverlapPolicy,
+    MisfirePolicy,
+    Timezone,
+    Enabled,
+    RetryConfig,
     DelFlag,
     UpdateTime,
     CreateTime,
@@ -70,6 +70,23 @@ pub enum SyncLog {
     RunningLog,
     SyncConfigId,
     Status,
+    Reconciliation,
+    TaskId,
+    RunId,
+    NodeId,
+    Attempt,
+    MaxAttempts,
+    RetryOf,
+    Partial,
+    StartedAt,
+    FinishedAt,
+    DurationMs,
+    RequestCount,
+    RowsFetched,
+    RowsWritten,
+    RowsRejected,
+    Bytes,
+    ErrorClass,
     UpdateTime,
     CreateTime,
 }
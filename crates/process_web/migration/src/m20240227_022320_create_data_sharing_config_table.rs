@@ -77,6 +77,7 @@ pub enum DataSharingConfig {
     TableName,
     QuerySql,
     DataSourceId,
+    MaskRules,
     DelFlag,
     UpdateTime,
     CreateTime,
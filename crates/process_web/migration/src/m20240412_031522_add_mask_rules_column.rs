@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000001_create_collect_config_table::CollectConfig;
+use crate::m20240227_022320_create_data_sharing_config_table::DataSharingConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .add_column(ColumnDef::new(CollectConfig::MaskRules).json().comment(
+                        r#"脱敏规则: [{"key": "data#phone", "policy": {"type": "phone"}}]"#,
+                    ))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(DataSharingConfig::Table)
+                    .add_column(
+                        ColumnDef::new(DataSharingConfig::MaskRules).json().comment(
+                            r#"按调用方区分的脱敏规则: [{"auth_ids": ["xxx"], "rules": []}]"#,
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
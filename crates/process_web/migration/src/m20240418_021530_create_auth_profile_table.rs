@@ -0,0 +1,86 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000001_create_collect_config_table::CollectConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuthProfile::Table)
+                    .comment("采集任务的认证配置")
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuthProfile::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AuthProfile::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(AuthProfile::AuthConfig)
+                            .json()
+                            .not_null()
+                            .comment(r#"认证配置，其中的密钥加密存储: {"type": "basic", "username": "", "password": ""}"#),
+                    )
+                    .col(ColumnDef::new(AuthProfile::Desc).string())
+                    .col(
+                        ColumnDef::new(AuthProfile::DelFlag)
+                            .integer()
+                            .default(0)
+                            .not_null()
+                            .comment(r#"1 已删除 0 未删除"#),
+                    )
+                    .col(ColumnDef::new(AuthProfile::UpdateTime).date_time().not_null())
+                    .col(ColumnDef::new(AuthProfile::CreateTime).date_time().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .add_column(
+                        ColumnDef::new(CollectConfig::AuthProfileId)
+                            .integer()
+                            .comment(r#"请求时使用的认证配置"#),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .drop_column(CollectConfig::AuthProfileId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(AuthProfile::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AuthProfile {
+    Table,
+    Id,
+    Name,
+    AuthConfig,
+    ConnectionConfig,
+    Desc,
+    DelFlag,
+    UpdateTime,
+    CreateTime,
+}
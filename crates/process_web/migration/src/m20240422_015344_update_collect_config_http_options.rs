@@ -0,0 +1,22 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000001_create_collect_config_table::CollectConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .add_column(ColumnDef::new(CollectConfig::HttpOptions).json().comment(
+                        r#"请求超时与重试配置: {"read_timeout": 60000, "max_retries": 3}"#,
+                    ))
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,24 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000001_create_collect_config_table::CollectConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .add_column(
+                        ColumnDef::new(CollectConfig::ResponseAssertion)
+                            .json()
+                            .comment(r#"返回数据校验: {"expected_statuses": [200], "success_path": "code", "success_value": "SUCCESS", "error_message_path": "msg"}"#),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,24 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000001_create_collect_config_table::CollectConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .add_column(
+                        ColumnDef::new(CollectConfig::PaginationConfig)
+                            .json()
+                            .comment(r#"分页策略: {"type": "page_number", "page_param": "page", "records_path": "data.list"}"#),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
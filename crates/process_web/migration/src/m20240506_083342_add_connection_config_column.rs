@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000001_create_collect_config_table::CollectConfig;
+use crate::m20240418_021530_create_auth_profile_table::AuthProfile;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .add_column(
+                        ColumnDef::new(CollectConfig::ConnectionConfig)
+                            .json()
+                            .comment(r#"连接配置: {"proxy": "socks5://127.0.0.1:1080", "ca_cert": "", "client_cert": "", "client_key": ""}"#),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuthProfile::Table)
+                    .add_column(
+                        ColumnDef::new(AuthProfile::ConnectionConfig)
+                            .json()
+                            .comment("连接配置，使用该认证配置的采集任务默认使用"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
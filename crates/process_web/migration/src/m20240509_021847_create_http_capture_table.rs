@@ -0,0 +1,118 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000001_create_collect_config_table::CollectConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(HttpCapture::Table)
+                    .comment("采集任务保存的原始请求与返回")
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(HttpCapture::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(HttpCapture::CollectConfigId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(HttpCapture::CollectLogId).integer())
+                    .col(ColumnDef::new(HttpCapture::Method).string().not_null())
+                    .col(ColumnDef::new(HttpCapture::Url).text().not_null())
+                    .col(
+                        ColumnDef::new(HttpCapture::RequestHeaders)
+                            .json()
+                            .not_null()
+                            .comment("认证相关的请求头会被替换为******"),
+                    )
+                    .col(ColumnDef::new(HttpCapture::RequestBody).text())
+                    .col(ColumnDef::new(HttpCapture::Status).integer().not_null())
+                    .col(
+                        ColumnDef::new(HttpCapture::ResponseHeaders)
+                            .json()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(HttpCapture::ResponseBody).text().not_null())
+                    .col(
+                        ColumnDef::new(HttpCapture::Truncated)
+                            .boolean()
+                            .default(false)
+                            .not_null()
+                            .comment("返回内容超过max_body_size被截断，截断后无法重放"),
+                    )
+                    .col(
+                        ColumnDef::new(HttpCapture::CreateTime)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_http_capture_collect_config_id")
+                    .table(HttpCapture::Table)
+                    .col(HttpCapture::CollectConfigId)
+                    .col(HttpCapture::CreateTime)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .add_column(
+                        ColumnDef::new(CollectConfig::CaptureConfig)
+                            .json()
+                            .comment(r#"保存原始请求与返回: {"max_body_size": 1048576, "max_captures": 20, "retention_days": 7}"#),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .drop_column(CollectConfig::CaptureConfig)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(HttpCapture::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum HttpCapture {
+    Table,
+    Id,
+    CollectConfigId,
+    CollectLogId,
+    Method,
+    Url,
+    RequestHeaders,
+    RequestBody,
+    Status,
+    ResponseHeaders,
+    ResponseBody,
+    Truncated,
+    CreateTime,
+}
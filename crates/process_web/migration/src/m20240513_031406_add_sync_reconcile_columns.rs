@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_023953_create_sync_config_table::SyncConfig;
+use crate::m20240119_030002_create_sync_log_table::SyncLog;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncConfig::Table)
+                    .add_column(
+                        ColumnDef::new(SyncConfig::ReconcileConfig)
+                            .json()
+                            .comment(r#"同步后核对配置: {"target_query_sql": "select * from t where ...", "key_columns": ["id"]}"#),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncLog::Table)
+                    .add_column(
+                        ColumnDef::new(SyncLog::Reconciliation)
+                            .json()
+                            .comment("同步后源表与目标表的核对结果"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,24 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_030002_create_sync_log_table::SyncLog;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncLog::Table)
+                    .add_column(
+                        ColumnDef::new(SyncLog::TaskId)
+                            .string()
+                            .comment(r#"正在执行的任务的id"#),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000001_create_collect_config_table::CollectConfig;
+use crate::m20240119_023953_create_sync_config_table::SyncConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .add_column(
+                        ColumnDef::new(CollectConfig::Priority)
+                            .integer()
+                            .comment("任务优先级，数值越大越先执行"),
+                    )
+                    .add_column(
+                        ColumnDef::new(CollectConfig::OverlapPolicy)
+                            .string()
+                            .comment(
+                                "上一次任务未结束时的处理方式: skip跳过、queue排队、allow同时执行",
+                            ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncConfig::Table)
+                    .add_column(
+                        ColumnDef::new(SyncConfig::Priority)
+                            .integer()
+                            .comment("任务优先级，数值越大越先执行"),
+                    )
+                    .add_column(ColumnDef::new(SyncConfig::OverlapPolicy).string().comment(
+                        "上一次任务未结束时的处理方式: skip跳过、queue排队、allow同时执行",
+                    ))
+                    .to_owned(),
+            )
+            .await
+    }
+}
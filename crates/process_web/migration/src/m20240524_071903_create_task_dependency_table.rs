@@ -0,0 +1,170 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000002_create_collect_log_table::CollectLog;
+use crate::m20240119_030002_create_sync_log_table::SyncLog;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskDependency::Table)
+                    .comment("采集与同步任务之间的依赖，上游任务结束后触发下游任务")
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TaskDependency::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TaskDependency::UpstreamKind)
+                            .string()
+                            .not_null()
+                            .comment("collect或者sync"),
+                    )
+                    .col(
+                        ColumnDef::new(TaskDependency::UpstreamId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TaskDependency::DownstreamKind)
+                            .string()
+                            .not_null()
+                            .comment("collect或者sync"),
+                    )
+                    .col(
+                        ColumnDef::new(TaskDependency::DownstreamId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TaskDependency::TriggerOn)
+                            .string()
+                            .not_null()
+                            .comment("success上游成功时触发、failure上游失败时触发、always都触发"),
+                    )
+                    .col(
+                        ColumnDef::new(TaskDependency::UpdateTime)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TaskDependency::CreateTime)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskRun::Table)
+                    .comment("有下游依赖的任务每次从定时或者手动执行开始的一次运行")
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TaskRun::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TaskRun::RunId)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(TaskRun::Kind).string().not_null())
+                    .col(ColumnDef::new(TaskRun::ConfigId).integer().not_null())
+                    .col(ColumnDef::new(TaskRun::CreateTime).date_time().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectLog::Table)
+                    .add_column(
+                        ColumnDef::new(CollectLog::RunId)
+                            .string()
+                            .comment("同一次依赖链运行中的任务使用相同的run_id"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncLog::Table)
+                    .add_column(
+                        ColumnDef::new(SyncLog::RunId)
+                            .string()
+                            .comment("同一次依赖链运行中的任务使用相同的run_id"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncLog::Table)
+                    .drop_column(SyncLog::RunId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectLog::Table)
+                    .drop_column(CollectLog::RunId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(TaskRun::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(TaskDependency::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TaskDependency {
+    Table,
+    Id,
+    UpstreamKind,
+    UpstreamId,
+    DownstreamKind,
+    DownstreamId,
+    TriggerOn,
+    UpdateTime,
+    CreateTime,
+}
+
+#[derive(DeriveIden)]
+enum TaskRun {
+    Table,
+    Id,
+    RunId,
+    Kind,
+    ConfigId,
+    CreateTime,
+}
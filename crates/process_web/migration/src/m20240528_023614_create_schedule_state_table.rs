@@ -0,0 +1,167 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000001_create_collect_config_table::CollectConfig;
+use crate::m20240119_000002_create_collect_log_table::CollectLog;
+use crate::m20240119_023953_create_sync_config_table::SyncConfig;
+use crate::m20240119_030002_create_sync_log_table::SyncLog;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ScheduleState::Table)
+                    .comment("定时任务的触发记录，多个节点通过它保证同一次触发只执行一次")
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ScheduleState::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduleState::Kind)
+                            .string()
+                            .not_null()
+                            .comment("collect或者sync"),
+                    )
+                    .col(ColumnDef::new(ScheduleState::ConfigId).integer().not_null())
+                    .col(
+                        ColumnDef::new(ScheduleState::LastFireTime)
+                            .date_time()
+                            .comment("最近一次被执行的计划触发时间"),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduleState::NodeId)
+                            .string()
+                            .comment("执行最近一次触发的节点"),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduleState::UpdateTime)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduleState::CreateTime)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_schedule_state_kind_config_id")
+                            .col(ScheduleState::Kind)
+                            .col(ScheduleState::ConfigId)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .add_column(ColumnDef::new(CollectConfig::MisfirePolicy).string().comment(
+                        "停机期间错过的定时任务的处理方式: once补执行一次、all全部补执行、skip跳过",
+                    ))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncConfig::Table)
+                    .add_column(ColumnDef::new(SyncConfig::MisfirePolicy).string().comment(
+                        "停机期间错过的定时任务的处理方式: once补执行一次、all全部补执行、skip跳过",
+                    ))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectLog::Table)
+                    .add_column(
+                        ColumnDef::new(CollectLog::NodeId)
+                            .string()
+                            .comment("执行任务的节点"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncLog::Table)
+                    .add_column(
+                        ColumnDef::new(SyncLog::NodeId)
+                            .string()
+                            .comment("执行任务的节点"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncLog::Table)
+                    .drop_column(SyncLog::NodeId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectLog::Table)
+                    .drop_column(CollectLog::NodeId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncConfig::Table)
+                    .drop_column(SyncConfig::MisfirePolicy)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .drop_column(CollectConfig::MisfirePolicy)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ScheduleState::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum ScheduleState {
+    Table,
+    Id,
+    Kind,
+    ConfigId,
+    LastFireTime,
+    NodeId,
+    UpdateTime,
+    CreateTime,
+}
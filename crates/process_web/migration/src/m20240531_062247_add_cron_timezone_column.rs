@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000001_create_collect_config_table::CollectConfig;
+use crate::m20240119_023953_create_sync_config_table::SyncConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .add_column(
+                        ColumnDef::new(CollectConfig::Timezone)
+                            .string()
+                            .comment("cron使用的IANA时区，如Asia/Shanghai，为空时使用服务器时区"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncConfig::Table)
+                    .add_column(
+                        ColumnDef::new(SyncConfig::Timezone)
+                            .string()
+                            .comment("cron使用的IANA时区，如Asia/Shanghai，为空时使用服务器时区"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncConfig::Table)
+                    .drop_column(SyncConfig::Timezone)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .drop_column(CollectConfig::Timezone)
+                    .to_owned(),
+            )
+            .await
+    }
+}
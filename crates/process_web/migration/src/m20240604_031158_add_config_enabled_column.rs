@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000001_create_collect_config_table::CollectConfig;
+use crate::m20240119_023953_create_sync_config_table::SyncConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .add_column(
+                        ColumnDef::new(CollectConfig::Enabled)
+                            .boolean()
+                            .not_null()
+                            .default(true)
+                            .comment("是否启用定时任务，暂停时保留cron"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncConfig::Table)
+                    .add_column(
+                        ColumnDef::new(SyncConfig::Enabled)
+                            .boolean()
+                            .not_null()
+                            .default(true)
+                            .comment("是否启用定时任务，暂停时保留cron"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncConfig::Table)
+                    .drop_column(SyncConfig::Enabled)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .drop_column(CollectConfig::Enabled)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,140 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000001_create_collect_config_table::CollectConfig;
+use crate::m20240119_000002_create_collect_log_table::CollectLog;
+use crate::m20240119_023953_create_sync_config_table::SyncConfig;
+use crate::m20240119_030002_create_sync_log_table::SyncLog;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .add_column(
+                        ColumnDef::new(CollectConfig::RetryConfig).json().comment(
+                            "失败后的重试策略，查看service::task_retry_service::RetryConfig",
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncConfig::Table)
+                    .add_column(
+                        ColumnDef::new(SyncConfig::RetryConfig).json().comment(
+                            "失败后的重试策略，查看service::task_retry_service::RetryConfig",
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectLog::Table)
+                    .add_column(
+                        ColumnDef::new(CollectLog::Attempt)
+                            .integer()
+                            .comment("第几次执行，重试时递增"),
+                    )
+                    .add_column(
+                        ColumnDef::new(CollectLog::MaxAttempts)
+                            .integer()
+                            .comment("最多执行的次数"),
+                    )
+                    .add_column(
+                        ColumnDef::new(CollectLog::RetryOf)
+                            .integer()
+                            .comment("重试时为第一次执行的日志id"),
+                    )
+                    .add_column(
+                        ColumnDef::new(CollectLog::Partial)
+                            .boolean()
+                            .comment("部分数据写入后失败"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncLog::Table)
+                    .add_column(
+                        ColumnDef::new(SyncLog::Attempt)
+                            .integer()
+                            .comment("第几次执行，重试时递增"),
+                    )
+                    .add_column(
+                        ColumnDef::new(SyncLog::MaxAttempts)
+                            .integer()
+                            .comment("最多执行的次数"),
+                    )
+                    .add_column(
+                        ColumnDef::new(SyncLog::RetryOf)
+                            .integer()
+                            .comment("重试时为第一次执行的日志id"),
+                    )
+                    .add_column(
+                        ColumnDef::new(SyncLog::Partial)
+                            .boolean()
+                            .comment("部分数据写入后失败"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncLog::Table)
+                    .drop_column(SyncLog::Attempt)
+                    .drop_column(SyncLog::MaxAttempts)
+                    .drop_column(SyncLog::RetryOf)
+                    .drop_column(SyncLog::Partial)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectLog::Table)
+                    .drop_column(CollectLog::Attempt)
+                    .drop_column(CollectLog::MaxAttempts)
+                    .drop_column(CollectLog::RetryOf)
+                    .drop_column(CollectLog::Partial)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncConfig::Table)
+                    .drop_column(SyncConfig::RetryConfig)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectConfig::Table)
+                    .drop_column(CollectConfig::RetryConfig)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,200 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240119_000002_create_collect_log_table::CollectLog;
+use crate::m20240119_030002_create_sync_log_table::SyncLog;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RunEvent::Table)
+                    .comment("采集与同步任务执行过程中的事件")
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RunEvent::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(RunEvent::Kind)
+                            .string()
+                            .not_null()
+                            .comment("collect或者sync"),
+                    )
+                    .col(
+                        ColumnDef::new(RunEvent::LogId)
+                            .integer()
+                            .not_null()
+                            .comment("collect_log或者sync_log的id"),
+                    )
+                    .col(
+                        ColumnDef::new(RunEvent::Level)
+                            .string()
+                            .not_null()
+                            .comment("info、warn或者error"),
+                    )
+                    .col(ColumnDef::new(RunEvent::Message).text().not_null())
+                    .col(ColumnDef::new(RunEvent::CreateTime).date_time().not_null())
+                    .index(
+                        Index::create()
+                            .name("idx_run_event_kind_log_id")
+                            .col(RunEvent::Kind)
+                            .col(RunEvent::LogId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectLog::Table)
+                    .add_column(
+                        ColumnDef::new(CollectLog::StartedAt)
+                            .date_time()
+                            .comment("开始执行的时间，不包括排队的时间"),
+                    )
+                    .add_column(ColumnDef::new(CollectLog::FinishedAt).date_time())
+                    .add_column(
+                        ColumnDef::new(CollectLog::DurationMs)
+                            .big_integer()
+                            .comment("执行耗时，单位毫秒"),
+                    )
+                    .add_column(
+                        ColumnDef::new(CollectLog::RequestCount)
+                            .big_integer()
+                            .comment("发起的请求数"),
+                    )
+                    .add_column(
+                        ColumnDef::new(CollectLog::RowsFetched)
+                            .big_integer()
+                            .comment("获取到的数据条数"),
+                    )
+                    .add_column(
+                        ColumnDef::new(CollectLog::RowsWritten)
+                            .big_integer()
+                            .comment("写入成功的数据条数"),
+                    )
+                    .add_column(
+                        ColumnDef::new(CollectLog::RowsRejected)
+                            .big_integer()
+                            .comment("写入失败的数据条数"),
+                    )
+                    .add_column(
+                        ColumnDef::new(CollectLog::Bytes)
+                            .big_integer()
+                            .comment("接口返回内容的字节数"),
+                    )
+                    .add_column(
+                        ColumnDef::new(CollectLog::ErrorClass)
+                            .string()
+                            .comment("失败的类型，查看service::run_event_service::ErrorClass"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncLog::Table)
+                    .add_column(
+                        ColumnDef::new(SyncLog::StartedAt)
+                            .date_time()
+                            .comment("开始执行的时间，不包括排队的时间"),
+                    )
+                    .add_column(ColumnDef::new(SyncLog::FinishedAt).date_time())
+                    .add_column(
+                        ColumnDef::new(SyncLog::DurationMs)
+                            .big_integer()
+                            .comment("执行耗时，单位毫秒"),
+                    )
+                    .add_column(
+                        ColumnDef::new(SyncLog::RequestCount)
+                            .big_integer()
+                            .comment("执行的查询数"),
+                    )
+                    .add_column(
+                        ColumnDef::new(SyncLog::RowsFetched)
+                            .big_integer()
+                            .comment("从源数据库查询到的数据条数"),
+                    )
+                    .add_column(
+                        ColumnDef::new(SyncLog::RowsWritten)
+                            .big_integer()
+                            .comment("已提交的语句数"),
+                    )
+                    .add_column(
+                        ColumnDef::new(SyncLog::RowsRejected)
+                            .big_integer()
+                            .comment("未提交的语句数"),
+                    )
+                    .add_column(ColumnDef::new(SyncLog::Bytes).big_integer())
+                    .add_column(
+                        ColumnDef::new(SyncLog::ErrorClass)
+                            .string()
+                            .comment("失败的类型，查看service::run_event_service::ErrorClass"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SyncLog::Table)
+                    .drop_column(SyncLog::StartedAt)
+                    .drop_column(SyncLog::FinishedAt)
+                    .drop_column(SyncLog::DurationMs)
+                    .drop_column(SyncLog::RequestCount)
+                    .drop_column(SyncLog::RowsFetched)
+                    .drop_column(SyncLog::RowsWritten)
+                    .drop_column(SyncLog::RowsRejected)
+                    .drop_column(SyncLog::Bytes)
+                    .drop_column(SyncLog::ErrorClass)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CollectLog::Table)
+                    .drop_column(CollectLog::StartedAt)
+                    .drop_column(CollectLog::FinishedAt)
+                    .drop_column(CollectLog::DurationMs)
+                    .drop_column(CollectLog::RequestCount)
+                    .drop_column(CollectLog::RowsFetched)
+                    .drop_column(CollectLog::RowsWritten)
+                    .drop_column(CollectLog::RowsRejected)
+                    .drop_column(CollectLog::Bytes)
+                    .drop_column(CollectLog::ErrorClass)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(RunEvent::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RunEvent {
+    Table,
+    Id,
+    Kind,
+    LogId,
+    Level,
+    Message,
+    CreateTime,
+}
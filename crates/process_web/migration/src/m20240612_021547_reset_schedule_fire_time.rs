@@ -0,0 +1,33 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240528_023614_create_schedule_state_table::ScheduleState;
+
+/// last_fire_time改为保存UTC时间，之前按服务器时区保存的时间无法确定时差，
+/// 清空后不补执行本次升级期间错过的触发，从下一次触发开始重新记录
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(ScheduleState::Table)
+                    .value(ScheduleState::LastFireTime, Expr::cust("NULL"))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(ScheduleState::Table)
+                    .value(ScheduleState::LastFireTime, Expr::cust("NULL"))
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::{
+    extract::{Path, State},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use ts_rs::TS;
+
+use crate::api::common::{AppError, AppState, PaginationPayload, ResJson, ResJsonWithPagination};
+use crate::entity::auth_profile::Model;
+use crate::service::auth_profile_service::AuthProfileService;
+use crate::{bool_response, data_response, pagination_response};
+
+pub fn set_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/find_by_id/:id", get(find_by_id))
+        .route("/list", post(list))
+        .route("/add", post(add))
+        .route("/update_by_id/:id", post(update_by_id))
+        .route("/del/:id", get(del))
+}
+
+async fn find_by_id(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> Result<ResJson<Model>, AppError> {
+    let res = AuthProfileService::find_by_id(&state.conn, id).await;
+
+    data_response!(res)
+}
+
+#[derive(Deserialize, TS)]
+#[ts(
+    export,
+    export_to = "ui/api/models/auto-generates/AuthProfileListParams.ts",
+    rename = "AuthProfileListParams"
+)]
+pub struct ListParams {
+    pub name: Option<String>,
+}
+
+async fn list(
+    state: State<Arc<AppState>>,
+    Json(payload): Json<PaginationPayload<ListParams>>,
+) -> Result<ResJsonWithPagination<Model>, AppError> {
+    let res = AuthProfileService::list(
+        &state.conn,
+        payload.current,
+        payload.page_size,
+        payload.data,
+    )
+    .await;
+
+    pagination_response!(res, payload.current, payload.page_size)
+}
+
+async fn add(
+    state: State<Arc<AppState>>,
+    Json(payload): Json<Model>,
+) -> Result<ResJson<Model>, AppError> {
+    let res = AuthProfileService::add(&state, payload).await;
+
+    data_response!(res)
+}
+
+/// 密钥字段传入******时保持原值不变
+async fn update_by_id(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    Json(payload): Json<Model>,
+) -> Result<ResJson<Model>, AppError> {
+    let res = AuthProfileService::update_by_id(&state, id, payload).await;
+
+    data_response!(res)
+}
+
+async fn del(state: State<Arc<AppState>>, Path(id): Path<i32>) -> Result<ResJson<bool>, AppError> {
+    let res = AuthProfileService::delete(&state, id).await;
+
+    bool_response!(res)
+}
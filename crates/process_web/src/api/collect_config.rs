@@ -6,16 +6,15 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
+use sea_orm::DbErr;
 use serde::Deserialize;
-use tracing::debug;
 use ts_rs::TS;
-use uuid::Uuid;
 
-use crate::api::common::{
-    AppError, AppState, LogTask, PaginationPayload, ResJson, ResJsonWithPagination,
-};
+use crate::api::common::{AppError, AppState, PaginationPayload, ResJson, ResJsonWithPagination};
 use crate::entity::collect_config::Model;
-use crate::service::collect_config_service::CollectConfigService;
+use crate::service::collect_config_service::{
+    self, hide_secrets, CollectConfigService, PreviewResult,
+};
 use crate::{bool_response, data_response, pagination_response};
 
 pub fn set_routes() -> Router<Arc<AppState>> {
@@ -26,13 +25,18 @@ pub fn set_routes() -> Router<Arc<AppState>> {
         .route("/update_by_id/:id", post(update_by_id))
         .route("/del/:id", get(del))
         .route("/execute/:id", get(execute))
+        .route("/pause/:id", get(pause))
+        .route("/resume/:id", get(resume))
+        .route("/preview", post(preview))
 }
 
 async fn find_by_id(
     state: State<Arc<AppState>>,
     Path(id): Path<i32>,
 ) -> Result<ResJson<Model>, AppError> {
-    let res = CollectConfigService::find_by_id(&state.conn, id).await;
+    let res = CollectConfigService::find_by_id(&state.conn, id)
+        .await
+        .map(hide_secrets);
 
     data_response!(res)
 }
@@ -57,7 +61,8 @@ async fn list(
         payload.page_size,
         payload.data,
     )
-    .await;
+    .await
+    .map(|(list, total)| (list.into_iter().map(hide_secrets).collect(), total));
 
     pagination_response!(res, payload.current, payload.page_size)
 }
@@ -66,7 +71,9 @@ async fn add(
     state: State<Arc<AppState>>,
     Json(payload): Json<Model>,
 ) -> Result<ResJson<Model>, AppError> {
-    let res = CollectConfigService::add(state.0, payload).await;
+    let res = CollectConfigService::add(state.0, payload)
+        .await
+        .map(hide_secrets);
 
     data_response!(res)
 }
@@ -76,7 +83,9 @@ async fn update_by_id(
     Path(id): Path<i32>,
     Json(payload): Json<Model>,
 ) -> Result<ResJson<Model>, AppError> {
-    let res = CollectConfigService::update_by_id(state.0, id, payload).await;
+    let res = CollectConfigService::update_by_id(state.0, id, payload)
+        .await
+        .map(hide_secrets);
 
     data_response!(res)
 }
@@ -87,6 +96,32 @@ async fn del(state: State<Arc<AppState>>, Path(id): Path<i32>) -> Result<ResJson
     bool_response!(res)
 }
 
+#[derive(Deserialize, TS)]
+#[ts(
+    export,
+    export_to = "ui/api/models/auto-generates/CollectConfigPreviewParams.ts",
+    rename = "CollectConfigPreviewParams"
+)]
+pub struct PreviewParams {
+    /// 修改已保存的采集配置时传入，用于读取上一次采集成功的时间与隐藏的证书
+    pub id: Option<i32>,
+    pub config: Model,
+    /// 示例数据，为字符串时按response_config解析，未传入时请求接口获取第一页数据
+    #[ts(type = "any")]
+    pub sample: Option<serde_json::Value>,
+}
+
+/// 预览采集配置每个阶段处理后的数据，不会写入缓存表
+async fn preview(
+    state: State<Arc<AppState>>,
+    Json(payload): Json<PreviewParams>,
+) -> Result<ResJson<PreviewResult>, AppError> {
+    let res: anyhow::Result<PreviewResult> =
+        Ok(collect_config_service::preview(&state, payload).await);
+
+    data_response!(res)
+}
+
 /// 执行id所配置的采集任务
 pub async fn execute(
     state: State<Arc<AppState>>,
@@ -94,23 +129,31 @@ pub async fn execute(
 ) -> Result<ResJson<bool>, AppError> {
     let data = CollectConfigService::find_by_id(&state.conn, id).await?;
 
-    let task_id = Uuid::new_v4().simple();
-    let st = state.clone();
-    let log_task = LogTask::new();
-    let cloned_token = log_task.token.clone();
-    let mut task = state.log_task.write().await;
-    task.insert(task_id, log_task);
-    drop(task);
-
-    tokio::task::spawn(async move {
-        tokio::select! {
-            _ = cloned_token.cancelled() => {
-                // The token was cancelled, task can shut down
-                debug!("cloned_token {cloned_token:?}");
-            }
-            _ = CollectConfigService::execute_task(&st, &data, task_id) => {}
-        }
-    });
-    let res: anyhow::Result<bool> = Ok(true);
+    let res = CollectConfigService::enqueue_task(&state, &data, None, None)
+        .await
+        .and_then(|x| x.ok_or(DbErr::Custom("上一次任务仍在执行，本次跳过".to_owned())));
     bool_response!(res)
 }
+
+/// 暂停定时任务，保留cron
+async fn pause(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> Result<ResJson<Model>, AppError> {
+    let res = CollectConfigService::pause(state.0, id)
+        .await
+        .map(hide_secrets);
+
+    data_response!(res)
+}
+
+async fn resume(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> Result<ResJson<Model>, AppError> {
+    let res = CollectConfigService::resume(state.0, id)
+        .await
+        .map(hide_secrets);
+
+    data_response!(res)
+}
@@ -1,19 +1,26 @@
 use anyhow::Result;
 use axum::extract::{Path, State};
+use axum::response::sse::{Event, Sse};
 use axum::Json;
 use axum::{
     routing::{get, post},
     Router,
 };
+use futures_util::Stream;
 use serde::Deserialize;
 use std::sync::Arc;
 use tracing::error;
 use ts_rs::TS;
 use uuid::Uuid;
 
-use crate::api::common::{AppError, AppState, PaginationPayload, ResJson, ResJsonWithPagination};
+use crate::api::common::{
+    run_update_stream, AppError, AppState, PaginationPayload, ResJson, ResJsonWithPagination,
+};
 use crate::entity::collect_log::{self, Model};
+use crate::entity::run_event;
 use crate::service::collect_log_service::CollectLogService;
+use crate::service::run_event_service::{ErrorClass, RunEventService};
+use crate::service::task_executor_service::TaskKind;
 use crate::{bool_response, data_response, pagination_response};
 
 pub fn set_routes() -> Router<Arc<AppState>> {
@@ -24,6 +31,8 @@ pub fn set_routes() -> Router<Arc<AppState>> {
         .route("/update_by_id/:id", post(update_by_id))
         .route("/delete/:id", get(del))
         .route("/stop_task/:id", get(stop_task))
+        .route("/events/:id", get(events))
+        .route("/stream/:id", get(stream))
 }
 
 async fn find_by_id(
@@ -79,6 +88,42 @@ async fn update_by_id(
     data_response!(res)
 }
 
+/// 一次执行过程中的全部事件
+async fn events(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> Result<ResJson<Vec<run_event::Model>>, AppError> {
+    let res = RunEventService::list_by_log(&state.conn, TaskKind::Collect, id).await;
+
+    data_response!(res)
+}
+
+/// 以Server-Sent Events推送执行过程中的事件与进度，执行结束后关闭
+async fn stream(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, AppError> {
+    let subscribed_at = chrono::Local::now().naive_local();
+    let receiver = state.run_updates.subscribe();
+    let log = CollectLogService::find_by_id(&state.conn, id).await?;
+    let history = RunEventService::list_by_log(&state.conn, TaskKind::Collect, id).await?;
+    // 其他节点执行的任务收不到本节点的广播，需要轮询数据库
+    let remote = match log.node_id.as_deref() == Some(state.node_id.as_str()) {
+        true => None,
+        false => Some(state.conn.clone()),
+    };
+
+    Ok(run_update_stream(
+        TaskKind::Collect,
+        id,
+        log.status,
+        history,
+        receiver,
+        subscribed_at,
+        remote,
+    ))
+}
+
 async fn del(state: State<Arc<AppState>>, Path(id): Path<i32>) -> Result<ResJson<bool>, AppError> {
     let res = CollectLogService::delete(&state.conn, id).await;
 
@@ -93,12 +138,13 @@ pub async fn stop_task(
 
     let log_id = state.stop_log_task(log_task_id).await;
     if let Some(log_id) = log_id {
-        if let Err(err) = CollectLogService::update_by_id(
-            &state.conn,
+        if let Err(err) = CollectLogService::update_and_publish(
+            &state,
             log_id,
             collect_log::Model {
                 status: 5,
                 running_log: "用户手动停止".to_string(),
+                error_class: Some(ErrorClass::Stopped.as_str().to_string()),
                 ..Default::default()
             },
         )
@@ -2,19 +2,31 @@ use async_trait::async_trait;
 use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
 use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
+use chrono::NaiveDateTime;
+use futures_util::{stream, Stream, StreamExt};
+use process_core::auth::AuthProvider;
+use process_core::rate_limit::RateLimiters;
 use sea_orm::DatabaseConnection;
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
 use tokio::sync::RwLock;
 use tokio_cron_scheduler::JobScheduler;
 use tokio_util::sync::CancellationToken;
+use tracing::warn;
 use uuid::fmt::Simple;
 
+use crate::entity::run_event;
+use crate::service::run_event_service::{RunEventService, RunUpdate, RunUpdates};
+use crate::service::task_executor_service::{TaskExecutor, TaskKind};
+
 pub type ResJson<T> = Json<ResTemplate<T>>;
 
 pub type ResJsonWithPagination<T> = ResJson<Pagination<Vec<T>>>;
@@ -60,6 +72,16 @@ pub struct AppState {
     pub(crate) cache_conn: DatabaseConnection,
     pub(crate) sched: JobScheduler,
     pub(crate) log_task: Arc<RwLock<HashMap<Simple, LogTask>>>,
+    /// 以auth_profile的id缓存认证信息，多个采集任务共用同一个token
+    pub(crate) auth_providers: Arc<RwLock<HashMap<i32, Arc<AuthProvider>>>>,
+    /// 采集配置与上游host的限流器
+    pub(crate) rate_limiters: Arc<RateLimiters>,
+    /// 采集与同步任务的执行队列
+    pub(crate) executor: Arc<TaskExecutor>,
+    /// 当前节点的标识，多个节点共用一个数据库时区分任务由哪个节点执行
+    pub(crate) node_id: String,
+    /// 推送给实时查看日志的客户端
+    pub(crate) run_updates: RunUpdates,
 }
 
 impl AppState {
@@ -68,6 +90,7 @@ impl AppState {
         let mut log_id = None;
         if let Some(h) = task.get(&log_task_id) {
             h.token.cancel();
+            self.executor.cancel(&log_task_id.to_string());
             log_id = Some(h.log_id);
             drop(task);
             self.log_task.write().await.remove(&log_task_id);
@@ -108,6 +131,132 @@ where
     }
 }
 
+/// 其他节点执行的任务收不到广播，按此间隔从数据库查询新的事件与状态
+const RUN_UPDATE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 先推送已保存的事件，执行未结束时继续推送实时的事件与进度，执行结束后关闭
+/// receiver需要在查询history之前订阅，subscribed_at为订阅的时间
+/// 任务由其他节点执行时传入remote，改为轮询数据库，只推送事件与执行结束，没有进度
+pub fn run_update_stream(
+    kind: TaskKind,
+    log_id: i32,
+    status: i32,
+    history: Vec<run_event::Model>,
+    receiver: broadcast::Receiver<RunUpdate>,
+    subscribed_at: NaiveDateTime,
+    remote: Option<DatabaseConnection>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let finished = matches!(status, 2 | 3 | 5);
+    let last_id = history.last().map(|x| x.id).unwrap_or_default();
+    // 订阅之后保存的事件还会从广播中收到一次，需要跳过，数据库中的时间可能只精确到秒
+    let since = subscribed_at - chrono::Duration::seconds(1);
+    let duplicated = history
+        .iter()
+        .filter(|x| x.create_time >= since)
+        .map(|x| x.message.clone())
+        .collect::<VecDeque<String>>();
+    let mut saved = history
+        .into_iter()
+        .map(|x| RunUpdate::from_event(kind, x))
+        .collect::<Vec<RunUpdate>>();
+    if finished {
+        saved.push(RunUpdate::Finished {
+            kind,
+            log_id,
+            status,
+        });
+    }
+
+    let live = stream::unfold(
+        (receiver, duplicated, finished),
+        move |(mut receiver, mut duplicated, finished)| async move {
+            if finished {
+                return None;
+            }
+            loop {
+                match receiver.recv().await {
+                    Ok(update) if update.is_for(kind, log_id) => {
+                        if let RunUpdate::Event { message, .. } = &update {
+                            if duplicated.front() == Some(message) {
+                                duplicated.pop_front();
+                                continue;
+                            }
+                        }
+                        let finished = matches!(update, RunUpdate::Finished { .. });
+                        return Some((update, (receiver, duplicated, finished)));
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(count)) => {
+                        warn!("实时日志推送过慢，跳过了{count}条消息")
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        },
+    );
+
+    let live = match remote {
+        Some(db) if !finished => poll_run_updates(db, kind, log_id, last_id).boxed(),
+        _ => live.boxed(),
+    };
+
+    let stream = stream::iter(saved)
+        .chain(live)
+        .map(|x| Event::default().json_data(x));
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// 轮询数据库中id大于last_id的事件，日志状态变为2、3、5时推送执行结束后关闭
+fn poll_run_updates(
+    db: DatabaseConnection,
+    kind: TaskKind,
+    log_id: i32,
+    last_id: i32,
+) -> impl Stream<Item = RunUpdate> {
+    stream::unfold(
+        (db, VecDeque::new(), last_id, false),
+        move |(db, mut pending, mut last_id, mut finished)| async move {
+            loop {
+                if let Some(update) = pending.pop_front() {
+                    return Some((update, (db, pending, last_id, finished)));
+                }
+                if finished {
+                    return None;
+                }
+                tokio::time::sleep(RUN_UPDATE_POLL_INTERVAL).await;
+
+                // 先查询状态再查询事件，执行结束前保存的事件都能查询到
+                let status = match RunEventService::log_status(&db, kind, log_id).await {
+                    Ok(Some(x)) => x,
+                    Ok(None) => return None,
+                    Err(err) => {
+                        warn!("实时日志查询失败 {err}");
+                        continue;
+                    }
+                };
+                match RunEventService::list_after(&db, kind, log_id, last_id).await {
+                    Ok(list) => {
+                        last_id = list.last().map(|x| x.id).unwrap_or(last_id);
+                        pending.extend(list.into_iter().map(|x| RunUpdate::from_event(kind, x)));
+                    }
+                    Err(err) => {
+                        warn!("实时日志查询失败 {err}");
+                        continue;
+                    }
+                }
+                if matches!(status, 2 | 3 | 5) {
+                    pending.push_back(RunUpdate::Finished {
+                        kind,
+                        log_id,
+                        status,
+                    });
+                    finished = true;
+                }
+            }
+        },
+    )
+}
+
 #[derive(Deserialize)]
 pub struct PaginationPayload<T> {
     pub current: u64,
@@ -164,3 +313,53 @@ where
         Ok(RequestInfo(parts.clone()))
     }
 }
+
+#[tokio::test]
+async fn test_poll_run_updates() -> Result<(), sea_orm::DbErr> {
+    use crate::entity::{collect_config, collect_log};
+    use crate::service::run_event_service::EventLevel;
+    use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, DbBackend, Schema, Set};
+
+    let db = Database::connect("sqlite::memory:").await?;
+    let schema = Schema::new(DbBackend::Sqlite);
+    // 日志引用了采集配置，只需要建表
+    for table in [
+        schema.create_table_from_entity(collect_config::Entity),
+        schema.create_table_from_entity(collect_log::Entity),
+        schema.create_table_from_entity(run_event::Entity),
+    ] {
+        db.execute(db.get_database_backend().build(&table)).await?;
+    }
+    let now = chrono::Local::now().naive_local();
+    let log = collect_log::ActiveModel {
+        running_log: Set(String::new()),
+        status: Set(1),
+        node_id: Set(Some("other".to_string())),
+        update_time: Set(now),
+        create_time: Set(now),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await?;
+    let first =
+        RunEventService::add(&db, TaskKind::Collect, log.id, EventLevel::Info, "开始执行").await?;
+
+    // 其他节点执行结束后，从数据库中查询到新的事件与执行结束
+    RunEventService::add(&db, TaskKind::Collect, log.id, EventLevel::Info, "执行成功").await?;
+    collect_log::ActiveModel {
+        id: Set(log.id),
+        status: Set(2),
+        ..Default::default()
+    }
+    .update(&db)
+    .await?;
+
+    let list = poll_run_updates(db, TaskKind::Collect, log.id, first.id)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(list.len(), 2);
+    assert!(matches!(&list[0], RunUpdate::Event { message, .. } if message == "执行成功"));
+    assert!(matches!(list[1], RunUpdate::Finished { status: 2, .. }));
+
+    Ok(())
+}
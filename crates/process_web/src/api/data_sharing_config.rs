@@ -105,6 +105,7 @@ async fn del(
 ///  输出：
 ///  select id, from public.test_data where id > 1 limit 5;
 /// ```
+/// 返回的数据会根据调用方的auth_id选择mask_rules中对应的脱敏规则进行处理
 ///
 async fn get_data(
     state: State<Arc<AppState>>,
@@ -121,13 +122,15 @@ async fn get_data(
     }
     let id = i32::from_str(&api_id[..1])?;
     let api_id = api_id[1..].to_string();
+
+    let res =
+        DataSharingConfigService::get_data(&state.conn, api_id, &user_info.auth_id, payload).await;
+
     let user_info = json!({
         "user": user_info.auth_id
     })
     .to_string();
 
-    let res = DataSharingConfigService::get_data(&state.conn, api_id, payload).await;
-
     if let Err(err) = &res {
         log_map.insert("err".to_string(), Value::String(err.to_string()));
     }
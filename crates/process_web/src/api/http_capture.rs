@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use ts_rs::TS;
+
+use crate::api::common::*;
+use crate::entity::collect_config;
+use crate::entity::http_capture::Model;
+use crate::service::http_capture_service::HttpCaptureService;
+use crate::{bool_response, data_response, pagination_response};
+
+#[derive(Deserialize, TS)]
+#[ts(
+    export,
+    export_to = "ui/api/models/auto-generates/HttpCaptureListParams.ts",
+    rename = "HttpCaptureListParams"
+)]
+pub struct ListParams {
+    pub collect_config_id: Option<i32>,
+    pub collect_log_id: Option<i32>,
+}
+
+pub fn set_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/find_by_id/:id", get(find_by_id))
+        .route("/list", post(list))
+        .route("/del/:id", get(del))
+        .route("/replay/:id", post(replay))
+}
+
+async fn find_by_id(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<ResJson<Model>, AppError> {
+    let res = HttpCaptureService::find_by_id(&state.conn, id).await;
+
+    data_response!(res)
+}
+
+async fn list(
+    state: State<Arc<AppState>>,
+    Json(payload): Json<PaginationPayload<ListParams>>,
+) -> anyhow::Result<ResJsonWithPagination<Model>, AppError> {
+    let res = HttpCaptureService::list(
+        &state.conn,
+        payload.current,
+        payload.page_size,
+        payload.data,
+    )
+    .await;
+
+    pagination_response!(res, payload.current, payload.page_size)
+}
+
+async fn del(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<ResJson<bool>, AppError> {
+    let res = HttpCaptureService::delete(&state.conn, id).await;
+
+    bool_response!(res)
+}
+
+/// 使用保存的返回数据重新执行映射与导出，可以传入修改后未保存的采集配置进行调试
+async fn replay(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    payload: Option<Json<collect_config::Model>>,
+) -> anyhow::Result<ResJson<serde_json::Value>, AppError> {
+    let res = HttpCaptureService::replay(&state.conn, id, payload.map(|x| x.0)).await;
+
+    data_response!(res)
+}
@@ -2,6 +2,7 @@ use anyhow::Result;
 use axum::http::{StatusCode, Uri};
 use axum::{middleware, Router};
 use migration::{Migrator, MigratorTrait};
+use process_core::rate_limit::RateLimiters;
 use sea_orm::*;
 // use tokio::runtime::Handle;
 // use tokio::time::interval;
@@ -20,19 +21,27 @@ use crate::api::auth::jwt_middleware;
 use crate::api::common::AppState;
 use crate::service::collect_config_service::CollectConfigService;
 use crate::service::log_service::LogService;
+use crate::service::run_event_service::{ErrorClass, RunUpdates};
+use crate::service::schedule_service::node_id_from_env;
 use crate::service::sync_config_service::SyncConfigService;
+use crate::service::task_executor_service::{ExecutorConfig, TaskExecutor};
 
 mod auth;
+pub mod auth_profile;
 pub mod collect_config;
 pub mod collect_log;
 pub mod common;
 pub mod data_sharing_config;
 pub mod data_source_list;
+pub mod http_capture;
 pub mod mock;
+pub mod schedule;
 pub mod sharing_request_log;
 pub mod statistics;
 pub mod sync_config;
 pub mod sync_log;
+pub mod task_dependency;
+pub mod task_queue;
 
 #[tokio::main]
 pub async fn start() -> Result<()> {
@@ -77,11 +86,26 @@ pub async fn start() -> Result<()> {
         cache_conn,
         sched,
         log_task,
+        auth_providers: Arc::new(RwLock::new(HashMap::new())),
+        rate_limiters: Arc::new(RateLimiters::new()),
+        executor: Arc::new(TaskExecutor::new(ExecutorConfig::from_env())),
+        node_id: node_id_from_env(),
+        run_updates: RunUpdates::new(1024),
     });
 
     // 初始化调度任务
-    LogService::reset_log_status(&state.conn, 0, 5, "任务因系统重启中断").await?;
-    LogService::reset_log_status(&state.conn, 1, 5, "任务因系统重启中断").await?;
+    // 只处理本节点的日志，其他节点的任务可能还在执行
+    for status in [0, 1, 4] {
+        LogService::reset_log_status(
+            &state.conn,
+            &state.node_id,
+            status,
+            5,
+            "任务因系统重启中断",
+            ErrorClass::Interrupted,
+        )
+        .await?;
+    }
     CollectConfigService::setup_collect_config_cron(&state).await?;
     SyncConfigService::setup_collect_config_cron(&state).await?;
     state.sched.start().await?;
@@ -91,10 +115,15 @@ pub async fn start() -> Result<()> {
     // build our application with a route
     let app = Router::new()
         .nest("/auth", auth::set_routes())
+        .nest("/auth_profile", auth_profile::set_routes())
         .nest("/collect_config", collect_config::set_routes())
         .nest("/collect_log", collect_log::set_routes())
+        .nest("/http_capture", http_capture::set_routes())
+        .nest("/schedule", schedule::set_routes())
         .nest("/sync_config", sync_config::set_routes())
         .nest("/sync_log", sync_log::set_routes())
+        .nest("/task_dependency", task_dependency::set_routes())
+        .nest("/task_queue", task_queue::set_routes())
         .nest("/data_source_list", data_source_list::set_routes())
         .nest("/data_sharing_config", data_sharing_config::set_routes())
         .nest("/sharing_request_log", sharing_request_log::set_routes())
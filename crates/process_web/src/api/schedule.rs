@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use axum::extract::Query;
+use axum::routing::get;
+use axum::Router;
+use serde::Deserialize;
+use ts_rs::TS;
+
+use crate::api::common::{AppError, AppState, ResJson};
+use crate::data_response;
+use crate::service::schedule_service::{CronPreview, ScheduleService};
+
+pub fn set_routes() -> Router<Arc<AppState>> {
+    Router::new().route("/validate", get(validate))
+}
+
+#[derive(Deserialize, TS)]
+#[ts(
+    export,
+    export_to = "ui/api/models/auto-generates/CronValidateParams.ts",
+    rename = "CronValidateParams"
+)]
+pub struct ValidateParams {
+    pub cron: String,
+    pub timezone: Option<String>,
+    /// 返回接下来的触发次数，默认为5，最多100
+    pub count: Option<usize>,
+}
+
+/// 校验cron与时区，返回接下来的触发时间
+async fn validate(Query(params): Query<ValidateParams>) -> Result<ResJson<CronPreview>, AppError> {
+    let count = params.count.unwrap_or(5).min(100);
+    let res = ScheduleService::validate(&params.cron, params.timezone.as_deref(), count);
+
+    data_response!(res)
+}
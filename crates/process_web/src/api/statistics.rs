@@ -7,9 +7,10 @@ use axum::routing::get;
 use axum::{routing::post, Json, Router};
 use chrono::{Local, NaiveDateTime, TimeZone};
 use migration::Condition;
+use sea_orm::sea_query::Expr;
 use sea_orm::{
-    ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, FromQueryResult, JsonValue, QueryFilter,
-    QueryOrder, QuerySelect, Statement,
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult,
+    JsonValue, Order, QueryFilter, QueryOrder, QuerySelect, Statement,
 };
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize};
@@ -28,6 +29,89 @@ fn get_native_date_by_timestamp(timestamp: i64) -> NaiveDateTime {
     Local.timestamp_millis_opt(timestamp).unwrap().naive_local()
 }
 
+/// SUM在MySQL与Postgres中返回decimal，转换为整数后才能按i64读取
+fn sum_as_integer(backend: DbBackend, column: &str) -> Result<String> {
+    let integer_type = match backend {
+        DbBackend::MySql => "SIGNED",
+        DbBackend::Postgres => "BIGINT",
+        _ => return Err(anyhow!("不支持的数据库格式")),
+    };
+
+    Ok(format!(
+        "CAST(COALESCE(SUM({column}), 0) AS {integer_type})"
+    ))
+}
+
+/// 一段时间内任务执行的数据量与吞吐量
+#[derive(Serialize, Deserialize, FromQueryResult, Default, Debug, PartialEq, TS)]
+#[ts(
+    export,
+    export_to = "ui/api/models/auto-generates/RunThroughput.ts",
+    rename = "RunThroughput"
+)]
+pub struct RunThroughput {
+    pub run_count: i64,
+    pub request_count: i64,
+    pub rows_fetched: i64,
+    pub rows_written: i64,
+    pub rows_rejected: i64,
+    pub bytes: i64,
+    /// 全部执行的耗时之和，单位毫秒
+    pub duration_ms: i64,
+    /// 每秒写入的数据条数
+    #[sea_orm(skip)]
+    pub rows_per_second: f64,
+}
+
+impl RunThroughput {
+    fn with_rate(mut self) -> Self {
+        if self.duration_ms > 0 {
+            self.rows_per_second = self.rows_written as f64 * 1000.0 / self.duration_ms as f64;
+        }
+        self
+    }
+}
+
+/// 统计collect_log或者sync_log中的执行记录
+async fn run_throughput(
+    conn: &DatabaseConnection,
+    table_name: &str,
+    start_date: NaiveDateTime,
+    end_date: NaiveDateTime,
+) -> Result<RunThroughput> {
+    let backend = conn.get_database_backend();
+    let (start, end) = match backend {
+        DbBackend::MySql => ("?", "?"),
+        _ => ("$1", "$2"),
+    };
+    let mut columns = vec!["COUNT(id) AS run_count".to_string()];
+    for column in [
+        "request_count",
+        "rows_fetched",
+        "rows_written",
+        "rows_rejected",
+        "bytes",
+        "duration_ms",
+    ] {
+        columns.push(format!("{} AS {column}", sum_as_integer(backend, column)?));
+    }
+    let query = format!(
+        "SELECT {} FROM {table_name} WHERE update_time > {start} AND update_time < {end}",
+        columns.join(", ")
+    );
+
+    let res = RunThroughput::find_by_statement(Statement::from_sql_and_values(
+        backend,
+        query,
+        [start_date.into(), end_date.into()],
+    ))
+    .one(conn)
+    .await?
+    .unwrap_or_default();
+
+    Ok(res.with_rate())
+}
+
 pub fn set_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/collect_task_info", get(collect_task_info))
@@ -117,9 +201,10 @@ pub struct CollectTaskInfoRes {
     list: Vec<Value>,
     #[ts(type = "any")]
     rank_list: Vec<Value>,
+    throughput: RunThroughput,
 }
 
-/// 获取每日采集任务执行的次数
+/// 获取每日采集任务写入的数据量，num_items为写入成功的数据条数，run_count为执行次数
 pub async fn collect_task_info_day_list(
     state: State<Arc<AppState>>,
     Json(payload): Json<CollectTaskInfoDayListReq>,
@@ -133,12 +218,21 @@ pub async fn collect_task_info_day_list(
         .add(collect_log::Column::UpdateTime.gte(start_date))
         .add(collect_log::Column::UpdateTime.lte(end_date));
 
-    let query: &str = match state.conn.get_database_backend() {
-        DbBackend::MySql => {
+    let backend = state.conn.get_database_backend();
+    let rows_written = sum_as_integer(backend, "rows_written")?;
+    let rows_fetched = sum_as_integer(backend, "rows_fetched")?;
+    let rows_rejected = sum_as_integer(backend, "rows_rejected")?;
+    let bytes = sum_as_integer(backend, "bytes")?;
+    let query = match backend {
+        DbBackend::MySql => format!(
             "SELECT
                 DATE_FORMAT(update_time,
                 '%Y-%m-%d') AS date,
-                COUNT(id) AS num_items
+                COUNT(id) AS run_count,
+                {rows_written} AS num_items,
+                {rows_fetched} AS rows_fetched,
+                {rows_rejected} AS rows_rejected,
+                {bytes} AS bytes
             FROM
                 collect_log
             WHERE
@@ -148,12 +242,16 @@ pub async fn collect_task_info_day_list(
                 date
             ORDER BY
                 date"
-        }
-        DbBackend::Postgres => {
+        ),
+        DbBackend::Postgres => format!(
             "SELECT
                 TO_CHAR(update_time,
                 'YYYY-MM-DD') AS date,
-                COUNT(id) AS num_items
+                COUNT(id) AS run_count,
+                {rows_written} AS num_items,
+                {rows_fetched} AS rows_fetched,
+                {rows_rejected} AS rows_rejected,
+                {bytes} AS bytes
             FROM
                 collect_log
             WHERE
@@ -163,14 +261,14 @@ pub async fn collect_task_info_day_list(
                 date
             ORDER BY
                 date"
-        }
+        ),
         _ => {
             return Err(anyhow!("不支持的数据库格式").into());
         }
     };
 
     let list = JsonValue::find_by_statement(Statement::from_sql_and_values(
-        state.conn.get_database_backend(),
+        backend,
         query,
         [start_date.into(), end_date.into()],
     ))
@@ -181,18 +279,24 @@ pub async fn collect_task_info_day_list(
         .select_only()
         .column(collect_log::Column::CollectConfigId)
         .column(collect_config::Column::Name)
-        .column_as(collect_log::Column::Id.count(), "num_items")
+        .column_as(Expr::cust(rows_written), "num_items")
+        .column_as(collect_log::Column::Id.count(), "run_count")
         .inner_join(collect_config::Entity)
         .filter(conditions)
         .group_by(collect_log::Column::CollectConfigId)
         .group_by(collect_config::Column::Name)
-        .order_by_desc(collect_log::Column::Id.count())
+        .order_by(Expr::cust("num_items"), Order::Desc)
         .limit(10)
         .into_json()
         .all(&state.conn)
         .await?;
+    let throughput = run_throughput(&state.conn, "collect_log", start_date, end_date).await?;
 
-    let res: Result<CollectTaskInfoRes> = Ok(CollectTaskInfoRes { list, rank_list });
+    let res: Result<CollectTaskInfoRes> = Ok(CollectTaskInfoRes {
+        list,
+        rank_list,
+        throughput,
+    });
 
     data_response!(res)
 }
@@ -346,13 +450,16 @@ pub struct SyncTaskInfoReq {
     rename = "SyncTaskInfoRes"
 )]
 pub struct SyncTaskInfoRes {
-    list: HashMap<String, i32>,
+    /// 每日提交的语句数
+    list: HashMap<String, i64>,
+    /// 全部同步任务提交的语句数
     num_items: i64,
     #[ts(type = "any")]
     rank_list: Vec<Value>,
+    throughput: RunThroughput,
 }
 
-/// 获取同步任务调用详情
+/// 获取同步任务写入的数据量
 pub async fn sync_task_info(
     state: State<Arc<AppState>>,
     Json(payload): Json<SyncTaskInfoReq>,
@@ -382,20 +489,18 @@ pub async fn sync_task_info(
         .all(&state.conn)
         .await?;
 
-    let mut info_day_map: HashMap<String, i32> = HashMap::new();
+    let mut info_day_map: HashMap<String, i64> = HashMap::new();
 
     for item in list {
         let date = item.update_time.format("%Y-%m-%d").to_string();
 
-        info_day_map
-            .entry(date)
-            .and_modify(|number| *number += 1)
-            .or_insert(1);
+        *info_day_map.entry(date).or_default() += item.rows_written.unwrap_or_default();
     }
 
+    let rows_written = sum_as_integer(state.conn.get_database_backend(), "rows_written")?;
     let count_res = sync_log::Entity::find()
         .select_only()
-        .column_as(sync_log::Column::Id.count(), "num_items")
+        .column_as(Expr::cust(rows_written.clone()), "num_items")
         .into_model::<NumItems>()
         .one(&state.conn)
         .await?;
@@ -412,21 +517,30 @@ pub async fn sync_task_info(
         .select_only()
         .column(sync_log::Column::SyncConfigId)
         .column(sync_config::Column::Name)
-        .column_as(sync_log::Column::Id.count(), "num_items")
+        .column_as(Expr::cust(rows_written), "num_items")
+        .column_as(sync_log::Column::Id.count(), "run_count")
         .inner_join(sync_config::Entity)
         .filter(conditions)
         .group_by(sync_log::Column::SyncConfigId)
         .group_by(sync_config::Column::Name)
-        .order_by_desc(sync_log::Column::Id.count())
+        .order_by(Expr::cust("num_items"), Order::Desc)
         .limit(10)
         .into_json()
         .all(&state.conn)
         .await?;
+    let throughput = run_throughput(
+        &state.conn,
+        "sync_log",
+        get_native_date_by_timestamp(payload.date[0]),
+        get_native_date_by_timestamp(payload.date[1]),
+    )
+    .await?;
 
     let res: Result<SyncTaskInfoRes> = Ok(SyncTaskInfoRes {
         list: info_day_map,
         num_items,
         rank_list,
+        throughput,
     });
 
     data_response!(res)
@@ -1,14 +1,15 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::routing::{get, post};
 use axum::{Json, Router};
+use sea_orm::DbErr;
 use serde::Deserialize;
 use std::sync::Arc;
 use ts_rs::TS;
 
 use crate::api::common::*;
 use crate::entity::sync_config::Model;
-use crate::service::sync_config_service::SyncConfigService;
-use crate::{bool_response, data_response, pagination_response, res_template_ok};
+use crate::service::sync_config_service::{PreviewResult, SyncConfigService};
+use crate::{bool_response, data_response, pagination_response};
 
 #[derive(Deserialize, TS)]
 #[ts(
@@ -20,6 +21,17 @@ pub struct ListParams {
     pub name: Option<String>,
 }
 
+#[derive(Deserialize, TS)]
+#[ts(
+    export,
+    export_to = "ui/api/models/auto-generates/SyncConfigPreviewParams.ts",
+    rename = "SyncConfigPreviewParams"
+)]
+pub struct PreviewParams {
+    /// 预览的行数，默认为10
+    pub limit: Option<u64>,
+}
+
 pub fn set_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/find_by_id/:id", get(find_by_id))
@@ -27,7 +39,12 @@ pub fn set_routes() -> Router<Arc<AppState>> {
         .route("/add", post(add))
         .route("/update_by_id/:id", post(update_by_id))
         .route("/del/:id", get(del))
+        .route("/preview/:id", get(preview))
         .route("/execute/:id", get(execute))
+        .route("/pause/:id", get(pause))
+        .route("/resume/:id", get(resume))
+        .route("/pause_by_data_source/:id", get(pause_by_data_source))
+        .route("/resume_by_data_source/:id", get(resume_by_data_source))
 }
 
 async fn find_by_id(
@@ -81,16 +98,65 @@ async fn del(
     bool_response!(res)
 }
 
-/// 执行id所配置的采集任务
+/// 预览同步配置：只查询前几行数据并生成目标语句，不写入目标数据库
+async fn preview(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    Query(params): Query<PreviewParams>,
+) -> anyhow::Result<ResJson<PreviewResult>, AppError> {
+    let res = SyncConfigService::preview(&state.conn, id, params.limit.unwrap_or(10)).await;
+
+    data_response!(res)
+}
+
+/// 执行id所配置的同步任务
 pub async fn execute(
     state: State<Arc<AppState>>,
     Path(id): Path<i32>,
 ) -> anyhow::Result<ResJson<bool>, AppError> {
     let data = SyncConfigService::find_by_id(&state.conn, id).await?;
 
-    tokio::task::spawn(async move {
-        SyncConfigService::execute_task(&state, &data).await;
-    });
+    let res = SyncConfigService::enqueue_task(&state, &data, None, None)
+        .await
+        .and_then(|x| x.ok_or(DbErr::Custom("上一次任务仍在执行，本次跳过".to_owned())));
+    bool_response!(res)
+}
+
+/// 暂停定时任务，保留cron
+async fn pause(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<ResJson<Model>, AppError> {
+    let res = SyncConfigService::pause(state.0, id).await;
+
+    data_response!(res)
+}
+
+async fn resume(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<ResJson<Model>, AppError> {
+    let res = SyncConfigService::resume(state.0, id).await;
 
-    Ok(Json(res_template_ok!(Some(true))))
+    data_response!(res)
+}
+
+/// 暂停源或者目标为该数据源的全部同步任务，返回被暂停的配置id
+async fn pause_by_data_source(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<ResJson<Vec<i32>>, AppError> {
+    let res = SyncConfigService::pause_by_data_source(state.0, id).await;
+
+    data_response!(res)
+}
+
+/// 恢复源或者目标为该数据源且已暂停的同步任务，返回被恢复的配置id
+async fn resume_by_data_source(
+    state: State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> anyhow::Result<ResJson<Vec<i32>>, AppError> {
+    let res = SyncConfigService::resume_by_data_source(state.0, id).await;
+
+    data_response!(res)
 }
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+
+use crate::api::common::{AppError, AppState, PaginationPayload, ResJson, ResJsonWithPagination};
+use crate::entity::task_dependency::Model;
+use crate::entity::task_run;
+use crate::service::task_dependency_service::{Dag, RunStep, TaskDependencyService};
+use crate::{bool_response, data_response, pagination_response};
+
+pub fn set_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/list", get(list))
+        .route("/add", post(add))
+        .route("/delete/:id", get(del))
+        .route("/dag", get(dag))
+        .route("/run_list", post(run_list))
+        .route("/run/:run_id", get(run_steps))
+}
+
+async fn list(state: State<Arc<AppState>>) -> Result<ResJson<Vec<Model>>, AppError> {
+    let res = TaskDependencyService::list(&state.conn).await;
+
+    data_response!(res)
+}
+
+async fn add(
+    state: State<Arc<AppState>>,
+    Json(payload): Json<Model>,
+) -> Result<ResJson<Model>, AppError> {
+    let res = TaskDependencyService::add(&state.conn, payload).await;
+
+    data_response!(res)
+}
+
+async fn del(state: State<Arc<AppState>>, Path(id): Path<i32>) -> Result<ResJson<bool>, AppError> {
+    let res = TaskDependencyService::delete(&state.conn, id).await;
+
+    bool_response!(res)
+}
+
+/// 采集、同步任务之间的依赖关系图
+async fn dag(state: State<Arc<AppState>>) -> Result<ResJson<Dag>, AppError> {
+    let res = TaskDependencyService::dag(&state.conn).await;
+
+    data_response!(res)
+}
+
+async fn run_list(
+    state: State<Arc<AppState>>,
+    Json(payload): Json<PaginationPayload<serde_json::Value>>,
+) -> Result<ResJsonWithPagination<task_run::Model>, AppError> {
+    let res =
+        TaskDependencyService::run_list(&state.conn, payload.current, payload.page_size).await;
+
+    pagination_response!(res, payload.current, payload.page_size)
+}
+
+/// 一次运行中各个任务的执行情况
+async fn run_steps(
+    state: State<Arc<AppState>>,
+    Path(run_id): Path<String>,
+) -> Result<ResJson<Vec<RunStep>>, AppError> {
+    let res = TaskDependencyService::run_steps(&state.conn, &run_id).await;
+
+    data_response!(res)
+}
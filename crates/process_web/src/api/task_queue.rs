@@ -0,0 +1,20 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::routing::get;
+use axum::Router;
+
+use crate::api::common::{AppError, AppState, ResJson};
+use crate::data_response;
+use crate::service::task_executor_service::TaskInfo;
+
+pub fn set_routes() -> Router<Arc<AppState>> {
+    Router::new().route("/list", get(list))
+}
+
+/// 执行器中正在执行与排队的任务
+async fn list(state: State<Arc<AppState>>) -> Result<ResJson<Vec<TaskInfo>>, AppError> {
+    let res = anyhow::Ok(state.executor.list());
+
+    data_response!(res)
+}
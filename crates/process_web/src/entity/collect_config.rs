@@ -36,7 +36,41 @@ pub struct Model {
     pub db_columns_config: Option<Json>,
     #[ts(type = "any")]
     pub db_columns_config2: Option<Json>,
+    #[ts(type = "any")]
+    pub mask_rules: Option<Json>,
+    #[ts(type = "any")]
+    pub response_config: Option<Json>,
+    pub auth_profile_id: Option<i32>,
+    #[ts(type = "any")]
+    pub http_options: Option<Json>,
+    #[ts(type = "any")]
+    pub response_assertion: Option<Json>,
+    #[ts(type = "any")]
+    pub pagination_config: Option<Json>,
+    #[ts(type = "any")]
+    pub steps: Option<Json>,
+    /// 查看process_core::http::ConnectionConfig，未配置时使用认证配置中的连接配置
+    #[ts(type = "any")]
+    pub connection_config: Option<Json>,
+    /// 查看service::http_capture_service::CaptureConfig，为空时不保存原始请求与返回
+    #[ts(type = "any")]
+    pub capture_config: Option<Json>,
+    /// 任务优先级，数值越大越先执行，默认为0
+    pub priority: Option<i32>,
+    /// 上一次任务未结束时的处理方式: skip、queue、allow，默认为queue
+    pub overlap_policy: Option<String>,
+    /// 停机期间错过的定时任务的处理方式: once、all、skip，默认为once
+    pub misfire_policy: Option<String>,
+    /// 查看service::task_retry_service::RetryConfig，为空时失败后不重试
+    #[ts(type = "any")]
+    pub retry_config: Option<Json>,
+    /// 5个字段的cron、6或7个字段的cron、@daily等预设或者every 15m这样的间隔
     pub cron: Option<String>,
+    /// cron使用的IANA时区，如Asia/Shanghai，为空时使用服务器时区
+    pub timezone: Option<String>,
+    /// 是否启用定时任务，通过pause、resume接口修改，暂停时保留cron
+    #[serde(skip_deserializing)]
+    pub enabled: bool,
     pub job_id: Option<Uuid>,
     #[serde(skip_deserializing)]
     pub update_time: DateTime,
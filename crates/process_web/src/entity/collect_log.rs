@@ -17,6 +17,44 @@ pub struct Model {
     pub id: i32,
     pub collect_config_id: Option<i32>,
     pub task_id: Option<String>,
+    pub run_id: Option<String>,
+    /// 执行任务的节点
+    pub node_id: Option<String>,
+    /// 第几次执行，与max_attempts一起显示为2/3
+    pub attempt: Option<i32>,
+    pub max_attempts: Option<i32>,
+    /// 重试时为第一次执行的日志id
+    pub retry_of: Option<i32>,
+    /// 部分数据写入后失败
+    pub partial: Option<bool>,
+    /// 开始执行的时间，不包括排队的时间
+    #[serde(skip_deserializing)]
+    #[ts(type = "string | null")]
+    pub started_at: Option<DateTime>,
+    #[serde(skip_deserializing)]
+    #[ts(type = "string | null")]
+    pub finished_at: Option<DateTime>,
+    /// 执行耗时，单位毫秒
+    #[serde(skip_deserializing)]
+    #[ts(type = "number | null")]
+    pub duration_ms: Option<i64>,
+    /// 发起的请求数
+    #[ts(type = "number | null")]
+    pub request_count: Option<i64>,
+    /// 接口返回处理后的数据条数
+    #[ts(type = "number | null")]
+    pub rows_fetched: Option<i64>,
+    /// 写入缓存数据库成功的数据条数
+    #[ts(type = "number | null")]
+    pub rows_written: Option<i64>,
+    /// 写入缓存数据库失败的数据条数
+    #[ts(type = "number | null")]
+    pub rows_rejected: Option<i64>,
+    /// 接口返回内容的字节数
+    #[ts(type = "number | null")]
+    pub bytes: Option<i64>,
+    /// 失败的类型，查看service::run_event_service::ErrorClass
+    pub error_class: Option<String>,
     #[sea_orm(column_type = "Text")]
     pub running_log: String,
     pub status: i32,
@@ -20,6 +20,8 @@ pub struct Model {
     pub query_sql: String,
     pub data_source_id: i32,
     pub api_id: Option<String>,
+    #[ts(type = "any")]
+    pub mask_rules: Option<Json>,
     #[serde(skip_deserializing)]
     pub del_flag: i32,
     #[serde(skip_deserializing)]
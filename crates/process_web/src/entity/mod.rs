@@ -2,10 +2,16 @@
 
 pub mod prelude;
 
+pub mod auth_profile;
 pub mod collect_config;
 pub mod collect_log;
 pub mod data_sharing_config;
 pub mod data_source_list;
+pub mod http_capture;
+pub mod run_event;
+pub mod schedule_state;
 pub mod sharing_request_log;
 pub mod sync_config;
 pub mod sync_log;
+pub mod task_dependency;
+pub mod task_run;
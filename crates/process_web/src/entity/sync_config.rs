@@ -24,8 +24,26 @@ pub struct Model {
     pub target_table_name: String,
     #[ts(type = "any")]
     pub target_query_sql_template: String,
+    /// 5个字段的cron、6或7个字段的cron、@daily等预设或者every 15m这样的间隔
     pub cron: Option<String>,
+    /// cron使用的IANA时区，如Asia/Shanghai，为空时使用服务器时区
+    pub timezone: Option<String>,
+    /// 是否启用定时任务，通过pause、resume接口修改，暂停时保留cron
+    #[serde(skip_deserializing)]
+    pub enabled: bool,
     pub job_id: Option<Uuid>,
+    /// 查看service::sync_config_service::ReconcileConfig，为空时同步后不做核对
+    #[ts(type = "any")]
+    pub reconcile_config: Option<Json>,
+    /// 任务优先级，数值越大越先执行，默认为0
+    pub priority: Option<i32>,
+    /// 上一次任务未结束时的处理方式: skip、queue、allow，默认为queue
+    pub overlap_policy: Option<String>,
+    /// 停机期间错过的定时任务的处理方式: once、all、skip，默认为once
+    pub misfire_policy: Option<String>,
+    /// 查看service::task_retry_service::RetryConfig，为空时失败后不重试
+    #[ts(type = "any")]
+    pub retry_config: Option<Json>,
     #[serde(skip_deserializing)]
     pub del_flag: i32,
     #[serde(skip_deserializing)]
@@ -19,6 +19,47 @@ pub struct Model {
     pub running_log: String,
     pub status: i32,
     pub sync_config_id: i32,
+    /// 查看service::sync_config_service::Reconciliation
+    #[ts(type = "any")]
+    pub reconciliation: Option<Json>,
+    pub task_id: Option<String>,
+    pub run_id: Option<String>,
+    /// 执行任务的节点
+    pub node_id: Option<String>,
+    /// 第几次执行，与max_attempts一起显示为2/3
+    pub attempt: Option<i32>,
+    pub max_attempts: Option<i32>,
+    /// 重试时为第一次执行的日志id
+    pub retry_of: Option<i32>,
+    /// 部分数据写入后失败
+    pub partial: Option<bool>,
+    /// 开始执行的时间，不包括排队的时间
+    #[serde(skip_deserializing)]
+    #[ts(type = "string | null")]
+    pub started_at: Option<DateTime>,
+    #[serde(skip_deserializing)]
+    #[ts(type = "string | null")]
+    pub finished_at: Option<DateTime>,
+    /// 执行耗时，单位毫秒
+    #[serde(skip_deserializing)]
+    #[ts(type = "number | null")]
+    pub duration_ms: Option<i64>,
+    /// 执行的查询数
+    #[ts(type = "number | null")]
+    pub request_count: Option<i64>,
+    /// 从源数据库查询到的数据条数
+    #[ts(type = "number | null")]
+    pub rows_fetched: Option<i64>,
+    /// 已提交的语句数
+    #[ts(type = "number | null")]
+    pub rows_written: Option<i64>,
+    /// 未提交的语句数
+    #[ts(type = "number | null")]
+    pub rows_rejected: Option<i64>,
+    #[ts(type = "number | null")]
+    pub bytes: Option<i64>,
+    /// 失败的类型，查看service::run_event_service::ErrorClass
+    pub error_class: Option<String>,
     #[serde(skip_deserializing)]
     pub update_time: DateTime,
     #[serde(skip_deserializing)]
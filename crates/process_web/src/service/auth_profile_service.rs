@@ -0,0 +1,193 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use process_core::auth::{AuthConfig, AuthProvider};
+use process_core::http::ConnectionConfig;
+use sea_orm::ActiveValue::{Set, Unchanged};
+use sea_orm::*;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::api::auth_profile::ListParams;
+use crate::api::common::AppState;
+use crate::entity::auth_profile;
+use crate::entity::auth_profile::Model;
+use crate::utils::{
+    decrypt_json_secrets, encrypt_json_secrets, hide_json_secrets, parse_connection_config,
+    CONNECTION_SECRET_FIELDS,
+};
+
+/// auth_config中需要加密存储的字段
+const SECRET_FIELDS: [&str; 4] = ["client_secret", "password", "secret", "body"];
+
+pub struct AuthProfileService;
+
+impl AuthProfileService {
+    pub async fn find_by_id(db: &DbConn, id: i32) -> Result<Model, DbErr> {
+        let data = AuthProfileService::find_raw_by_id(db, id).await?;
+
+        Ok(hide_secrets(data))
+    }
+
+    async fn find_raw_by_id(db: &DbConn, id: i32) -> Result<Model, DbErr> {
+        auth_profile::Entity::find_by_id(id)
+            .filter(auth_profile::Column::DelFlag.eq(0))
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find data by id.".to_owned()))
+    }
+
+    pub async fn list(
+        db: &DbConn,
+        page: u64,
+        page_size: u64,
+        data: Option<ListParams>,
+    ) -> Result<(Vec<Model>, u64), DbErr> {
+        let mut conditions = Condition::all();
+        if let Some(data) = data {
+            if let Some(name) = data.name {
+                conditions = conditions.add(auth_profile::Column::Name.contains(name));
+            }
+        }
+
+        let paginator = auth_profile::Entity::find()
+            .filter(auth_profile::Column::DelFlag.eq(0))
+            .filter(conditions)
+            .order_by_desc(auth_profile::Column::Id)
+            .paginate(db, page_size);
+
+        let num_pages = paginator.num_items().await?;
+
+        paginator
+            .fetch_page(page - 1)
+            .await
+            .map(|p| (p.into_iter().map(hide_secrets).collect(), num_pages))
+    }
+
+    pub async fn add(state: &AppState, data: Model) -> Result<Model, DbErr> {
+        AuthProfileService::save(state, None, data).await
+    }
+
+    pub async fn update_by_id(state: &AppState, id: i32, data: Model) -> Result<Model, DbErr> {
+        AuthProfileService::save(state, Some(id), data).await
+    }
+
+    pub async fn save(state: &AppState, id: Option<i32>, data: Model) -> Result<Model, DbErr> {
+        debug!("id: {:?}, name: {}", id, data.name);
+        let db = &state.conn;
+        let now = chrono::Local::now().naive_local();
+
+        let db_data = match id {
+            Some(id) => Some(AuthProfileService::find_raw_by_id(db, id).await?),
+            None => None,
+        };
+        let auth_config = encrypt_json_secrets(
+            data.auth_config,
+            db_data.as_ref().map(|x| &x.auth_config),
+            &SECRET_FIELDS,
+        )
+        .map_err(|err| DbErr::Custom(err.to_string()))?;
+        // 确保保存的配置能被正确解析
+        decrypt_auth_config(&auth_config).map_err(|err| DbErr::Custom(err.to_string()))?;
+        let connection_config = match data.connection_config {
+            Some(x) => Some(
+                encrypt_json_secrets(
+                    x,
+                    db_data.as_ref().and_then(|x| x.connection_config.as_ref()),
+                    &CONNECTION_SECRET_FIELDS,
+                )
+                .map_err(|err| DbErr::Custom(err.to_string()))?,
+            ),
+            None => None,
+        };
+        if let Some(x) = &connection_config {
+            parse_connection_config(x).map_err(|err| DbErr::Custom(err.to_string()))?;
+        }
+
+        let mut active_data = auth_profile::ActiveModel {
+            name: Set(data.name),
+            auth_config: Set(auth_config),
+            connection_config: Set(connection_config),
+            desc: Set(data.desc),
+            ..Default::default()
+        };
+
+        let res = match db_data {
+            Some(db_data) => {
+                active_data.id = Unchanged(db_data.id);
+                active_data.update_time = Set(now);
+                let res = active_data.update(db).await?;
+                state.auth_providers.write().await.remove(&res.id);
+                res
+            }
+            None => {
+                active_data.create_time = Set(now);
+                active_data.update_time = Set(now);
+                active_data.insert(db).await?
+            }
+        };
+
+        Ok(hide_secrets(res))
+    }
+
+    pub async fn delete(state: &AppState, id: i32) -> Result<Model, DbErr> {
+        let data = AuthProfileService::find_raw_by_id(&state.conn, id).await?;
+
+        let mut active_data = data.into_active_model();
+        active_data.del_flag = Set(1);
+        let res = active_data.update(&state.conn).await?;
+        state.auth_providers.write().await.remove(&id);
+
+        Ok(hide_secrets(res))
+    }
+
+    /// 获取认证信息，同一个认证配置在配置修改前共用缓存的token
+    pub async fn get_provider(state: &AppState, id: i32) -> anyhow::Result<Arc<AuthProvider>> {
+        if let Some(provider) = state.auth_providers.read().await.get(&id) {
+            return Ok(provider.clone());
+        }
+
+        let data = AuthProfileService::find_raw_by_id(&state.conn, id)
+            .await
+            .map_err(|err| anyhow!("认证配置{id}获取失败: {err}"))?;
+        let provider = Arc::new(AuthProvider::new(decrypt_auth_config(&data.auth_config)?));
+        state
+            .auth_providers
+            .write()
+            .await
+            .entry(id)
+            .or_insert(provider.clone());
+
+        Ok(provider)
+    }
+
+    /// 获取认证配置中的连接配置
+    pub async fn get_connection_config(
+        db: &DbConn,
+        id: i32,
+    ) -> anyhow::Result<Option<ConnectionConfig>> {
+        let data = AuthProfileService::find_raw_by_id(db, id)
+            .await
+            .map_err(|err| anyhow!("认证配置{id}获取失败: {err}"))?;
+
+        data.connection_config
+            .as_ref()
+            .map(parse_connection_config)
+            .transpose()
+    }
+}
+
+fn decrypt_auth_config(config: &Value) -> anyhow::Result<AuthConfig> {
+    let config = decrypt_json_secrets(config, &SECRET_FIELDS)?;
+
+    serde_json::from_value(config).map_err(|err| anyhow!("auth_config 无法解析: {err}"))
+}
+
+fn hide_secrets(mut data: Model) -> Model {
+    hide_json_secrets(&mut data.auth_config, &SECRET_FIELDS);
+    if let Some(x) = data.connection_config.as_mut() {
+        hide_json_secrets(x, &CONNECTION_SECRET_FIELDS);
+    }
+
+    data
+}
@@ -1,28 +1,52 @@
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
 
-use crate::api::collect_config::ListParams;
+use crate::api::collect_config::{ListParams, PreviewParams};
 use anyhow::anyhow;
 use chrono::Local;
-use process_core::http::{HttpConfig, NestedConfig};
-use process_core::json::find_value;
+use futures_util::{stream, StreamExt};
+use process_core::decode::RecordDecoder;
+use process_core::http::{
+    decode_response, ConnectionConfig, Http, HttpConfig, HttpOptions, NestedConfig,
+    ResponseAssertion, ResponseConfig,
+};
+use process_core::json::{
+    check_map_rules, find_value, flat_nested_object_by_config, map_data, MapRuleDiagnostic,
+};
+use process_core::mask::mask_data;
+use process_core::pagination::{PaginationConfig, Paginator};
 use process_core::process::{Export, Receive, Serde};
+use process_core::step::{HttpStep, StepStage};
 use sea_orm::ActiveValue::{Set, Unchanged};
 use sea_orm::*;
 
+use serde::Serialize;
 use serde_json::json;
-use tokio_cron_scheduler::{Job, JobSchedulerError};
-use tracing::{debug, error, warn};
+use tokio_cron_scheduler::JobSchedulerError;
+use tracing::{debug, error, info, warn};
+use ts_rs::TS;
 use uuid::fmt::Simple;
 use uuid::Uuid;
 
 use crate::api::common::{pg_to_mysql_type, AppState, LogTask};
 use crate::entity::collect_config::Model;
 use crate::entity::{collect_config, collect_log};
+use crate::service::auth_profile_service::AuthProfileService;
 use crate::service::collect_log_service::CollectLogService;
-use crate::utils::{format_body_string, format_cron, job_err_to_db_err};
+use crate::service::http_capture_service::{parse_capture_config, HttpCaptureService};
+use crate::service::run_event_service::{ErrorClass, RunMetrics, RunUpdate};
+use crate::service::schedule_service::{MisfirePolicy, ScheduleService};
+use crate::service::task_dependency_service::TaskDependencyService;
+use crate::service::task_executor_service::{OverlapPolicy, TaskInfo, TaskKind};
+use crate::service::task_retry_service::{
+    max_attempts_of, parse_retry_config, RetryAttempt, RetryConfig, TaskRetryService,
+};
+use crate::utils::{
+    encrypt_json_secrets, hide_json_secrets, job_err_to_db_err, parse_connection_config,
+    parse_mask_rules, render_template, url_host, TemplateContext, CONNECTION_SECRET_FIELDS,
+};
 
 use super::table_service::TableService;
 
@@ -85,7 +109,44 @@ impl CollectConfigService {
         db_data.update(&state.conn).await
     }
 
-    pub async fn save(state: Arc<AppState>, id: Option<i32>, data: Model) -> Result<Model, DbErr> {
+    pub async fn save(
+        state: Arc<AppState>,
+        id: Option<i32>,
+        mut data: Model,
+    ) -> Result<Model, DbErr> {
+        if let Some(cron) = data.cron.as_ref() {
+            ScheduleService::validate(cron, data.timezone.as_deref(), 1)?;
+        }
+        if let Some(x) = data.retry_config.as_ref() {
+            parse_retry_config(x).map_err(|err| DbErr::Custom(err.to_string()))?;
+        }
+        if let Some(x) = data.mask_rules.as_ref() {
+            parse_mask_rules(x).map_err(|err| DbErr::Custom(err.to_string()))?;
+        }
+        if let Some(x) = data.response_config.as_ref() {
+            let response_config: ResponseConfig = serde_json::from_value(x.clone())
+                .map_err(|err| DbErr::Custom(format!("response_config 无法解析: {err}")))?;
+            RecordDecoder::new(&response_config).map_err(|err| DbErr::Custom(err.to_string()))?;
+        }
+        if let Some(connection_config) = data.connection_config.take() {
+            let db_connection_config = match id {
+                Some(id) => collect_config::Entity::find_by_id(id)
+                    .one(&state.conn)
+                    .await?
+                    .and_then(|x| x.connection_config),
+                None => None,
+            };
+            let connection_config = encrypt_json_secrets(
+                connection_config,
+                db_connection_config.as_ref(),
+                &CONNECTION_SECRET_FIELDS,
+            )
+            .map_err(|err| DbErr::Custom(err.to_string()))?;
+            parse_connection_config(&connection_config)
+                .map_err(|err| DbErr::Custom(err.to_string()))?;
+            data.connection_config = Some(connection_config);
+        }
+
         debug!("data: {:?}, id: {:?}", data, id);
         let now = Local::now().naive_local();
 
@@ -106,8 +167,22 @@ impl CollectConfigService {
             filed_of_result_data: Set(data_clone.filed_of_result_data),
             max_count_of_request: Set(data_clone.max_count_of_request),
             cron: Set(data_clone.cron),
+            timezone: Set(data_clone.timezone),
             db_columns_config: Set(data_clone.db_columns_config),
             db_columns_config2: Set(data_clone.db_columns_config2),
+            mask_rules: Set(data_clone.mask_rules),
+            response_config: Set(data_clone.response_config),
+            auth_profile_id: Set(data_clone.auth_profile_id),
+            http_options: Set(data_clone.http_options),
+            response_assertion: Set(data_clone.response_assertion),
+            pagination_config: Set(data_clone.pagination_config),
+            steps: Set(data_clone.steps),
+            connection_config: Set(data_clone.connection_config),
+            capture_config: Set(data_clone.capture_config),
+            priority: Set(data_clone.priority),
+            overlap_policy: Set(data_clone.overlap_policy),
+            misfire_policy: Set(data_clone.misfire_policy),
+            retry_config: Set(data_clone.retry_config),
             ..Default::default()
         };
 
@@ -194,6 +269,44 @@ impl CollectConfigService {
         }
     }
 
+    /// 暂停定时任务，移除调度器中的任务并保留cron
+    pub async fn pause(state: Arc<AppState>, id: i32) -> Result<Model, DbErr> {
+        let data = Self::find_by_id(&state.conn, id).await?;
+
+        if let Some(job_id) = data.job_id {
+            state
+                .sched
+                .remove(&job_id)
+                .await
+                .map_err(job_err_to_db_err)?;
+        }
+        let mut active_data = data.into_active_model();
+
+        active_data.enabled = Set(false);
+        active_data.job_id = Set(None);
+        active_data.update_time = Set(Local::now().naive_local());
+        active_data.update(&state.conn).await
+    }
+
+    /// 恢复定时任务，暂停期间错过的触发不补执行
+    pub async fn resume(state: Arc<AppState>, id: i32) -> Result<Model, DbErr> {
+        let data = Self::find_by_id(&state.conn, id).await?;
+        if data.enabled {
+            return Ok(data);
+        }
+
+        ScheduleService::claim_resume(&state.conn, TaskKind::Collect, id, &state.node_id).await?;
+        let job_id = create_job_scheduler(state.clone(), &data)
+            .await
+            .map_err(job_err_to_db_err)?;
+        let mut active_data = data.into_active_model();
+
+        active_data.enabled = Set(true);
+        active_data.job_id = Set(job_id);
+        active_data.update_time = Set(Local::now().naive_local());
+        active_data.update(&state.conn).await
+    }
+
     pub async fn delete(state: Arc<AppState>, id: i32) -> Result<Model, DbErr> {
         let data = collect_config::Entity::find_by_id(id)
             .one(&state.conn)
@@ -212,79 +325,83 @@ impl CollectConfigService {
         active_data.del_flag = Set(1);
         active_data.job_id = Set(None);
 
+        TaskDependencyService::delete_by_config(&state.conn, TaskKind::Collect, id).await?;
+        ScheduleService::delete_by_config(&state.conn, TaskKind::Collect, id).await?;
         active_data.update(&state.conn).await
     }
 
-    pub async fn cache_data(state: &Arc<AppState>, list: &[String]) -> Result<(), String> {
-        let mut err_msg = String::new();
-
-        for (i, item) in list.iter().enumerate() {
-            let sql_list = item
-                .split(';')
-                .map(|x| x.to_string())
-                .collect::<Vec<String>>();
-            for sql in sql_list {
-                if sql.is_empty() {
-                    continue;
-                }
-                match state
-                    .cache_conn
-                    .execute(Statement::from_string(
-                        state.cache_conn.get_database_backend(),
-                        sql.clone(),
-                    ))
-                    .await
-                {
-                    Ok(msg) => {
-                        debug!("{:?}", msg);
-                        // return Ok::<(), String>(());
-                    }
-                    Err(err) => {
-                        error!("sql {} {}", sql, err);
-                        err_msg.push_str(&format!("第{}条SQL执行失败，{} \n", i + 1, err));
-                        // return Err::<(), String>(format!("第{}条SQL执行失败，{} \n", i + 1, err));
+    /// 逐条执行插入缓存数据库的语句，返回全部语句执行成功的数据条数；
+    /// 执行失败的语句按retry_config等待后一起重试，已成功的语句不会重复执行
+    pub async fn cache_data(
+        state: &Arc<AppState>,
+        list: &[String],
+        retry: &RetryConfig,
+    ) -> Result<usize, CacheDataError> {
+        // 每条数据中还未执行成功的语句
+        let mut pending = list
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let sql_list = item
+                    .split(';')
+                    .filter(|x| !x.is_empty())
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>();
+                (i, sql_list)
+            })
+            .collect::<Vec<(usize, Vec<String>)>>();
+        let mut succeeded = 0;
+        let mut attempt = 1;
+
+        loop {
+            let mut err_msg = String::new();
+            let mut failed = vec![];
+            for (i, sql_list) in pending {
+                let mut rest = vec![];
+                for sql in sql_list {
+                    match state
+                        .cache_conn
+                        .execute(Statement::from_string(
+                            state.cache_conn.get_database_backend(),
+                            sql.clone(),
+                        ))
+                        .await
+                    {
+                        Ok(msg) => {
+                            debug!("{:?}", msg);
+                        }
+                        Err(err) => {
+                            error!("sql {} {}", sql, err);
+                            err_msg.push_str(&format!("第{}条SQL执行失败，{} \n", i + 1, err));
+                            rest.push(sql);
+                        }
                     }
                 }
-
-                // let state = state.clone();
-                // let handler = tokio::spawn(async move {
-                //     match state.cache_conn
-                //         .execute(Statement::from_string(
-                //             state.cache_conn.get_database_backend(),
-                //             sql.clone(),
-                //         ))
-                //         .await
-                //     {
-                //         Ok(msg) => {
-                //             debug!("{:?}", msg);
-                //             return Ok::<(), String>(());
-                //         }
-                //         Err(err) => {
-                //             error!("sql {}", sql);
-                //             return Err::<(), String>(format!("第{}条SQL执行失败，{} \n", i + 1, err));
-                //         }
-                //     }
-                // });
-                // handlers.push(handler);
+                match rest.is_empty() {
+                    true => succeeded += 1,
+                    false => failed.push((i, rest)),
+                }
             }
-        }
 
-        // for handler in handlers {
-        //    match handler.await {
-        //         Ok(res) => {
-        //             if let Err(err) = res {
-        //                 err_msg.push_str(err.as_str());
-        //             }
-        //         },
-        //         Err(err) => {
-        //             debug!("{}", err);
-        //         }
-        //    }
-        // }
-        if err_msg.is_empty() {
-            Ok(())
-        } else {
-            Err(err_msg)
+            if failed.is_empty() {
+                return Ok(succeeded);
+            }
+            if attempt >= retry.max_attempts {
+                return Err(CacheDataError {
+                    succeeded,
+                    rejected: failed.len(),
+                    message: err_msg,
+                });
+            }
+            let delay = retry.delay(attempt);
+            attempt += 1;
+            warn!(
+                "{}条数据写入缓存数据库失败，{}秒后进行第{attempt}次写入",
+                failed.len(),
+                delay.as_secs()
+            );
+            tokio::time::sleep(delay).await;
+            pending = failed;
         }
     }
 
@@ -397,49 +514,98 @@ impl CollectConfigService {
         Ok(true)
     }
 
-    pub async fn execute_task(state: &Arc<AppState>, data: &Model, task_id: Simple) {
-        let mut collect_log_model = collect_log::Model {
-            collect_config_id: Some(data.id),
-            status: 0,
-            task_id: Some(task_id.to_string()),
-            running_log: String::new(),
-            ..Default::default()
+    /// 创建等待执行的日志并将采集任务提交到执行器
+    /// 上一次任务未结束且overlap_policy为skip时不执行，返回None；
+    /// 作为下游任务被触发时传入上游任务的run_id，失败重试时传入retry
+    pub async fn enqueue_task(
+        state: &Arc<AppState>,
+        data: &Model,
+        run_id: Option<String>,
+        retry: Option<RetryAttempt>,
+    ) -> Result<Option<Simple>, DbErr> {
+        let task_id = Uuid::new_v4().simple();
+        let run_id = match run_id {
+            Some(run_id) => run_id,
+            None => {
+                TaskDependencyService::start_run(&state.conn, TaskKind::Collect, data.id).await?
+            }
+        };
+        let (attempt, max_attempts, retry_of) = match retry {
+            Some(x) => (x.attempt, x.max_attempts, Some(x.retry_of)),
+            None => (1, max_attempts_of(data.retry_config.as_ref()), None),
+        };
+        let running_log = match retry {
+            Some(_) => format!("第{attempt}/{max_attempts}次执行，任务已进入执行队列"),
+            None => "任务已进入执行队列".to_string(),
         };
+        let log = CollectLogService::add(
+            &state.conn,
+            collect_log::Model {
+                collect_config_id: Some(data.id),
+                status: 4,
+                task_id: Some(task_id.to_string()),
+                run_id: Some(run_id.clone()),
+                node_id: Some(state.node_id.clone()),
+                attempt: Some(attempt),
+                max_attempts: Some(max_attempts),
+                retry_of,
+                running_log,
+                ..Default::default()
+            },
+        )
+        .await?;
 
-        match CollectLogService::add(&state.conn, collect_log_model.clone()).await {
-            Ok(db_log_data) => {
-                println!("collect_log_model {collect_log_model:?}");
-                println!("db_log_data {db_log_data:?}");
-                collect_log_model = db_log_data;
-            }
-            Err(err) => {
-                error!("任务日志添加失败 {err}");
+        let mut log_task = LogTask::new();
+        log_task.set_log_id(log.id);
+        let token = log_task.token.clone();
+        state.log_task.write().await.insert(task_id, log_task);
+
+        let mut info = TaskInfo::new(
+            task_id.to_string(),
+            TaskKind::Collect,
+            data.id,
+            data.name.clone(),
+        );
+        info.log_id = log.id;
+        info.run_id = run_id.clone();
+        info.priority = data.priority.unwrap_or_default();
+        info.overlap_policy = OverlapPolicy::parse(data.overlap_policy.as_deref());
+        if let Some(host) = url_host(&data.url) {
+            info.resources.push(format!("host:{host}"));
+        }
+
+        let st = state.clone();
+        let item = data.clone();
+        let log_id = log.id;
+        let job = Box::pin(async move {
+            tokio::select! {
+                _ = token.cancelled() => {
+                    debug!("采集任务 {task_id} 已停止");
+                }
+                _ = CollectConfigService::execute_task(&st, &item, task_id, log_id) => {}
             }
+            TaskRetryService::on_task_finished(st, TaskKind::Collect, item.id, log_id, run_id)
+                .await;
+        });
+        if state.executor.submit(info, job) {
+            return Ok(Some(task_id));
         }
 
-        let log_id = collect_log_model.id;
-        let mut task = state.log_task.write().await;
-        if let Some(h) = task.get_mut(&task_id) {
-            h.set_log_id(log_id);
-        };
-        drop(task);
-
-        // let mut interval = interval(Duration::from_secs(5));
-        // TODO 排查执行任务过多时有任务不执行
-        // loop {
-        //     interval.tick().await;
-
-        //     let metrics = Handle::current().metrics();
-        //     let task = state.log_task.read().await;
-        //     println!("task {:?} task_count {}", task.len(), metrics.active_tasks_count());
-        //     let len = task.len();
-        //     drop(task);
-        //     if len <= 5 {
-        //         drop(interval);
-        //         break;
-        //     }
-        // }
+        state.log_task.write().await.remove(&task_id);
+        CollectLogService::update_and_publish(
+            state,
+            log.id,
+            collect_log::Model {
+                status: 5,
+                running_log: "上一次任务仍在执行，本次跳过".to_string(),
+                ..Default::default()
+            },
+        )
+        .await?;
+        Ok(None)
+    }
 
+    pub async fn execute_task(state: &Arc<AppState>, data: &Model, task_id: Simple, log_id: i32) {
         if let Some(table_name) = data.cache_table_name.as_ref() {
             let mut log = String::new();
             match TableService::table_exists(
@@ -482,8 +648,8 @@ impl CollectConfigService {
                 }
             }
 
-            match CollectLogService::update_by_id(
-                &state.conn,
+            match CollectLogService::update_and_publish(
+                state,
                 log_id,
                 collect_log::Model {
                     collect_config_id: Some(data.id),
@@ -501,7 +667,26 @@ impl CollectConfigService {
             }
         }
 
-        let _ = process_data(data, state, log_id).await;
+        if let Err(err) = process_data(data, state, log_id).await {
+            // 配置无法解析等错误发生时process_data还未更新日志状态
+            match CollectLogService::find_by_id(&state.conn, log_id).await {
+                Ok(log) if !matches!(log.status, 2 | 3 | 5) => {
+                    let model = collect_log::Model {
+                        status: 3,
+                        running_log: err.to_string(),
+                        error_class: Some(ErrorClass::Config.as_str().to_string()),
+                        ..Default::default()
+                    };
+                    if let Err(err) =
+                        CollectLogService::update_and_publish(state, log_id, model).await
+                    {
+                        error!("status: 3 运行完毕；日志更新失败: {err}");
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => error!("任务日志查询失败 {err}"),
+            }
+        }
         state.log_task.write().await.remove(&task_id);
     }
 
@@ -509,12 +694,48 @@ impl CollectConfigService {
     pub async fn setup_collect_config_cron(state: &Arc<AppState>) -> anyhow::Result<()> {
         let list = collect_config::Entity::find()
             .filter(collect_config::Column::DelFlag.eq(0))
+            .filter(collect_config::Column::Enabled.eq(true))
             .all(&state.conn)
             .await?;
 
         for item in list {
             let state = state.clone();
             let item = item.clone();
+            if let Some(cron) = item.cron.as_ref() {
+                // 旧版本保存的cron可能无法解析，跳过不影响其他任务
+                if let Err(err) = ScheduleService::validate(cron, item.timezone.as_deref(), 1) {
+                    error!("采集配置：{} 定时任务添加失败 {err}", item.name);
+                    continue;
+                }
+                // 补执行失败时只记录日志，不影响服务启动与其他任务
+                let missed = match ScheduleService::catch_up(
+                    &state.conn,
+                    TaskKind::Collect,
+                    item.id,
+                    cron,
+                    item.timezone.as_deref(),
+                    MisfirePolicy::parse(item.misfire_policy.as_deref()),
+                    &state.node_id,
+                )
+                .await
+                {
+                    Ok(x) => x,
+                    Err(err) => {
+                        error!("采集配置：{} 定时任务添加失败 {err}", item.name);
+                        continue;
+                    }
+                };
+                for fire_time in missed {
+                    info!(
+                        "采集配置：{} 补执行 {fire_time} UTC 错过的定时任务",
+                        item.name
+                    );
+                    if let Err(err) = Self::enqueue_task(&state, &item, None, None).await {
+                        error!("采集配置：{} 补执行失败 {err}", item.name);
+                        break;
+                    }
+                }
+            }
             let job_id = create_job_scheduler(state.clone(), &item).await?;
 
             Self::update_job_id_by_id(state, job_id, item.id).await?;
@@ -524,14 +745,75 @@ impl CollectConfigService {
     }
 }
 
+/// 插入缓存数据库时有语句执行失败
+#[derive(Debug)]
+pub struct CacheDataError {
+    /// 全部语句执行成功的数据条数
+    pub succeeded: usize,
+    /// 有语句执行失败的数据条数
+    pub rejected: usize,
+    pub message: String,
+}
+
+impl Display for CacheDataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// 带上本次执行累计数据量的采集日志
+fn metrics_log(status: i32, running_log: String, metrics: &RunMetrics) -> collect_log::Model {
+    collect_log::Model {
+        status,
+        running_log,
+        request_count: Some(metrics.request_count),
+        rows_fetched: Some(metrics.rows_fetched),
+        rows_written: Some(metrics.rows_written),
+        rows_rejected: Some(metrics.rows_rejected),
+        bytes: Some(metrics.bytes),
+        ..Default::default()
+    }
+}
+
+/// 推送采集进度，pages为已请求的页数
+fn publish_progress(state: &AppState, log_id: i32, pages: i32, metrics: &RunMetrics) {
+    state.run_updates.publish(RunUpdate::Progress {
+        kind: TaskKind::Collect,
+        log_id,
+        pages: pages as i64,
+        metrics: *metrics,
+    });
+}
+
+/// 返回给前端时隐藏连接配置中的证书
+pub fn hide_secrets(mut data: Model) -> Model {
+    if let Some(x) = data.connection_config.as_mut() {
+        hide_json_secrets(x, &CONNECTION_SECRET_FIELDS);
+    }
+
+    data
+}
+
 pub async fn process_data(
     data: &Model,
     state: &Arc<AppState>,
     log_id: i32,
 ) -> anyhow::Result<()> {
-    let body_string = format_body_string(data.body.as_ref());
-    if let Some(err) = CollectLogService::update_by_id(
-        &state.conn,
+    // 未配置重试时写入失败的语句不会重试
+    let retry_config = match &data.retry_config {
+        Some(x) => parse_retry_config(x)?,
+        None => RetryConfig::default(),
+    };
+    let mut template_context = TemplateContext {
+        watermark: CollectLogService::find_last_success_time(&state.conn, data.id).await?,
+        body: data
+            .body
+            .as_deref()
+            .and_then(|x| serde_json::from_str(x).ok()),
+        ..Default::default()
+    };
+    if let Some(err) = CollectLogService::update_and_publish(
+        state,
         log_id,
         collect_log::Model {
             status: 1,
@@ -545,7 +827,29 @@ pub async fn process_data(
         error!("status: 1 运行完毕；日志更新失败: {err}");
     };
 
-    if let Some(loop_request_by_pagination) = data.loop_request_by_pagination {
+    let mut paginator = match &data.pagination_config {
+        Some(x) => Some(Paginator::new(
+            serde_json::from_value::<PaginationConfig>(x.clone())
+                .map_err(|err| anyhow!("pagination_config 无法解析: {err}"))?,
+        )),
+        None => None,
+    };
+
+    if let Some(x) = &data.capture_config {
+        let config = parse_capture_config(x)?;
+        if let Err(err) =
+            HttpCaptureService::cleanup(&state.conn, data.id, config.retention_days).await
+        {
+            error!("过期的原始请求与返回清理失败: {err}");
+        }
+    }
+
+    // 配置了pagination_config时按其中的策略分页，否则按body中的_loop_counts分页
+    let loop_request_by_pagination = match paginator.is_some() {
+        true => Some(true),
+        false => data.loop_request_by_pagination,
+    };
+    if let Some(loop_request_by_pagination) = loop_request_by_pagination {
         if loop_request_by_pagination {
             let mut should_stop = false;
             let max_number_of_result_data = data
@@ -554,59 +858,54 @@ pub async fn process_data(
             let max_count_of_request = data
                 .max_count_of_request
                 .ok_or(anyhow!("请指定max_count_of_request"))?;
-            let body =
-                serde_json::from_str::<serde_json::Value>(body_string.clone().unwrap().as_str())
-                    .unwrap();
-
             let mut loop_counts = 0;
-            let mut re_request_times = 0;
             let mut data_res = vec![];
+            let mut metrics = RunMetrics::default();
+            // 中间批次是否写入失败
+            let mut cache_failed = false;
 
             debug!("开始进行分页请求，max_number_of_result_data: {max_number_of_result_data}, max_count_of_request: {max_count_of_request}");
             while !should_stop {
-                let mut body_string = body_string.clone().unwrap_or_default();
-                let mut new_string = body_string.as_str();
-                let mut map_str = HashMap::new();
-
-                while let Some(l_i) = new_string.find("${") {
-                    new_string = &new_string[l_i..];
-
-                    if let Some(r_i) = new_string.find('}') {
-                        let current_str = &new_string[..r_i + 1];
-                        let mut parameter_str = new_string[2..r_i].to_string();
-
-                        // 对表达式中的值进行计算_loop_counts
-                        if parameter_str.contains("_loop_counts") {
-                            parameter_str = parameter_str
-                                .replace("_loop_counts", loop_counts.to_string().as_str());
-
-                            for (key, value) in body.as_object().unwrap() {
-                                if parameter_str.contains(key) {
-                                    parameter_str =
-                                        parameter_str.replace(key, value.to_string().as_str());
-                                }
+                template_context.loop_counts = Some(loop_counts);
+
+                match collect_pages(
+                    state,
+                    data,
+                    &mut template_context,
+                    paginator.as_mut(),
+                    max_count_of_request - loop_counts,
+                    Some(log_id),
+                    &mut metrics,
+                )
+                .await
+                {
+                    Ok((has_next_page, res, request_count)) => {
+                        let new_vec = match res {
+                            Ok(x) => x,
+                            Err(err) => {
+                                let log = anyhow!("返回数据处理失败 {}", err);
+                                let model = collect_log::Model {
+                                    error_class: Some(ErrorClass::Process.as_str().to_string()),
+                                    partial: Some(metrics.rows_written > 0),
+                                    ..metrics_log(3, log.to_string(), &metrics)
+                                };
+                                if let Some(err) =
+                                    CollectLogService::update_and_publish(state, log_id, model)
+                                        .await
+                                        .err()
+                                {
+                                    error!("status: 3 运行完毕；日志更新失败: {err}");
+                                };
+
+                                return Err(log);
                             }
-                            parameter_str = math_parse::MathParse::parse(parameter_str.as_str())
-                                .unwrap()
-                                .solve_float(None)
-                                .unwrap_or(0.0)
-                                .to_string();
-                        }
-                        map_str.insert(parameter_str, current_str.to_string());
-                        new_string = &new_string[r_i..];
-                    }
-                }
-                for (value, value2) in map_str {
-                    body_string = body_string.replace(value2.as_str(), &value);
-                }
-
-                match collect_data_with_http(data, Some(body_string.to_string())).await {
-                    Ok((has_next_page, res)) => {
-                        let new_vec = res?;
+                        };
+                        metrics.rows_fetched += new_vec.len() as i64;
 
                         should_stop = !has_next_page;
                         data_res = [data_res, new_vec].concat();
-                        loop_counts += 1;
+                        loop_counts += request_count;
+                        publish_progress(state, log_id, loop_counts, &metrics);
 
                         if data_res.len() >= max_number_of_result_data as usize {
                             should_stop = true;
@@ -628,8 +927,8 @@ pub async fn process_data(
                             let log = format!("已累计发起{loop_counts}次请求，本轮采集{}条数据开始插入!\n 处理后的数据为", data_res.len());
                             collect_log_string.push_str(log.as_str());
                             collect_log_string.push_str(res_data_str.as_str());
-                            if let Some(err) = CollectLogService::update_by_id(
-                                &state.conn,
+                            if let Some(err) = CollectLogService::update_and_publish(
+                                state,
                                 log_id,
                                 collect_log::Model {
                                     status: 1,
@@ -643,30 +942,47 @@ pub async fn process_data(
                                 error!("status: 2 运行完毕；日志更新失败: {err}");
                             };
 
-                            match CollectConfigService::cache_data(state, &data_res).await {
-                                Ok(_) => {
-                                    if let Some(err) = CollectLogService::update_by_id(
-                                        &state.conn,
-                                        log_id,
-                                        collect_log::Model {
-                                            status: 2,
-                                            ..Default::default()
+                            match CollectConfigService::cache_data(state, &data_res, &retry_config)
+                                .await
+                            {
+                                Ok(n) => {
+                                    metrics.rows_written += n as i64;
+                                    publish_progress(state, log_id, loop_counts, &metrics);
+                                    let model = match cache_failed {
+                                        true => collect_log::Model {
+                                            error_class: Some(
+                                                ErrorClass::Write.as_str().to_string(),
+                                            ),
+                                            partial: Some(metrics.rows_written > 0),
+                                            ..metrics_log(
+                                                3,
+                                                "部分批次写入缓存数据库失败".to_string(),
+                                                &metrics,
+                                            )
                                         },
-                                    )
-                                    .await
-                                    .err()
+                                        false => metrics_log(2, String::new(), &metrics),
+                                    };
+                                    if let Some(err) =
+                                        CollectLogService::update_and_publish(state, log_id, model)
+                                            .await
+                                            .err()
                                     {
                                         error!("status: 2 运行完毕；日志更新失败: {err}");
                                     };
                                 }
                                 Err(err) => {
-                                    if let Some(err) = CollectLogService::update_by_id(
-                                        &state.conn,
+                                    metrics.rows_written += err.succeeded as i64;
+                                    metrics.rows_rejected += err.rejected as i64;
+                                    publish_progress(state, log_id, loop_counts, &metrics);
+                                    if let Some(err) = CollectLogService::update_and_publish(
+                                        state,
                                         log_id,
                                         collect_log::Model {
-                                            status: 3,
-                                            running_log: format!("\n {}", err),
-                                            ..Default::default()
+                                            error_class: Some(
+                                                ErrorClass::Write.as_str().to_string(),
+                                            ),
+                                            partial: Some(metrics.rows_written > 0),
+                                            ..metrics_log(3, err.to_string(), &metrics)
                                         },
                                     )
                                     .await
@@ -681,15 +997,16 @@ pub async fn process_data(
                         }
                     }
                     Err(err) => {
-                        let log = anyhow!("循环请求因为异常中断,将在3s后再次尝试发起请求,重新请求次数为{re_request_times}次,上限为5次 {}", err);
+                        // 请求的重试在Http中按http_options进行，这里不再重试
+                        let log = anyhow!("循环请求因为异常中断 {}", err);
                         debug!("{}", log);
-                        if let Some(err) = CollectLogService::update_by_id(
-                            &state.conn,
+                        if let Some(err) = CollectLogService::update_and_publish(
+                            state,
                             log_id,
                             collect_log::Model {
-                                status: 1,
-                                running_log: log.to_string(),
-                                ..Default::default()
+                                error_class: Some(ErrorClass::Source.as_str().to_string()),
+                                partial: Some(metrics.rows_written > 0),
+                                ..metrics_log(3, log.to_string(), &metrics)
                             },
                         )
                         .await
@@ -697,35 +1014,13 @@ pub async fn process_data(
                         {
                             error!("status: 3 运行完毕；日志更新失败: {err}");
                         };
-                        if re_request_times < 5 {
-                            re_request_times += 1;
-                            should_stop = false;
-                            tokio::time::sleep(Duration::from_secs(3)).await;
-                        } else {
-                            should_stop = true;
-                            let log = anyhow!("多次请求后依然失败 {} ", err);
-                            debug!("{}", log);
-                            if let Some(err) = CollectLogService::update_by_id(
-                                &state.conn,
-                                log_id,
-                                collect_log::Model {
-                                    status: 3,
-                                    running_log: log.to_string(),
-                                    ..Default::default()
-                                },
-                            )
-                            .await
-                            .err()
-                            {
-                                error!("status: 3 运行完毕；日志更新失败: {err}");
-                            };
-                        }
+
+                        return Err(log);
                     }
                 }
 
                 // 如果数据大于1000条就开始入库
                 if data_res.len() > 1000 || (loop_counts != 0 && loop_counts % 50 == 0) {
-                    re_request_times = 0;
                     let mut collect_log_string = String::new();
                     let mut res_data_str = String::new();
                     if let Some(str) = data_res.first() {
@@ -741,8 +1036,8 @@ pub async fn process_data(
                     );
                     collect_log_string.push_str(log.as_str());
                     collect_log_string.push_str(res_data_str.as_str());
-                    if let Some(err) = CollectLogService::update_by_id(
-                        &state.conn,
+                    if let Some(err) = CollectLogService::update_and_publish(
+                        state,
                         log_id,
                         collect_log::Model {
                             status: 1,
@@ -756,17 +1051,16 @@ pub async fn process_data(
                         error!("status: 1 运行完毕；日志更新失败: {err}");
                     };
 
-                    match CollectConfigService::cache_data(state, &data_res).await {
-                        Ok(_) => {}
+                    match CollectConfigService::cache_data(state, &data_res, &retry_config).await {
+                        Ok(n) => metrics.rows_written += n as i64,
                         Err(err) => {
-                            if let Some(err) = CollectLogService::update_by_id(
-                                &state.conn,
+                            metrics.rows_written += err.succeeded as i64;
+                            metrics.rows_rejected += err.rejected as i64;
+                            cache_failed = true;
+                            if let Some(err) = CollectLogService::update_and_publish(
+                                state,
                                 log_id,
-                                collect_log::Model {
-                                    status: 1,
-                                    running_log: format!("\n {}", err),
-                                    ..Default::default()
-                                },
+                                metrics_log(1, err.to_string(), &metrics),
                             )
                             .await
                             .err()
@@ -775,6 +1069,7 @@ pub async fn process_data(
                             };
                         }
                     };
+                    publish_progress(state, log_id, loop_counts, &metrics);
 
                     data_res.clear();
                 }
@@ -782,12 +1077,30 @@ pub async fn process_data(
         }
         Ok(())
     } else {
-        match collect_data_with_http(data, body_string.clone()).await {
-            Ok((_, res)) => {
+        let mut metrics = RunMetrics::default();
+        let request_counter = Arc::new(AtomicUsize::new(0));
+        let res = collect_data_with_http(
+            state,
+            data,
+            &mut template_context,
+            None,
+            Some(log_id),
+            Some(request_counter.clone()),
+        )
+        .await;
+        metrics.request_count = request_counter.load(Ordering::Relaxed) as i64;
+        match res {
+            Ok((_, res, bytes)) => {
+                metrics.bytes = bytes as i64;
                 let mut collect_log_string = String::new();
                 let mut res_data_str = String::new();
+                let mut status = 2;
+                let mut partial = None;
+                let mut error_class = None;
                 match res.as_ref() {
                     Ok(list) => {
+                        metrics.rows_fetched = list.len() as i64;
+                        publish_progress(state, log_id, 1, &metrics);
                         if let Some(str) = list.first() {
                             res_data_str.push_str(str);
                             res_data_str.push_str("......");
@@ -797,9 +1110,9 @@ pub async fn process_data(
                         let log = format!("本轮采集{}条数据开始插入!\n 处理后的数据为", list.len());
                         collect_log_string.push_str(log.as_str());
                         collect_log_string.push_str(res_data_str.as_str());
-    
-                        if let Some(err) = CollectLogService::update_by_id(
-                            &state.conn,
+
+                        if let Some(err) = CollectLogService::update_and_publish(
+                            state,
                             log_id,
                             collect_log::Model {
                                 status: 1,
@@ -812,30 +1125,38 @@ pub async fn process_data(
                         {
                             error!("status: 1 运行完毕；日志更新失败: {err}");
                         };
-    
+
                         collect_log_string = String::new();
-    
-                        match CollectConfigService::cache_data(state, list).await {
-                            Ok(_) => {}
+
+                        match CollectConfigService::cache_data(state, list, &retry_config).await {
+                            Ok(n) => metrics.rows_written = n as i64,
                             Err(err) => {
+                                status = 3;
+                                metrics.rows_written = err.succeeded as i64;
+                                metrics.rows_rejected = err.rejected as i64;
+                                partial = Some(err.succeeded > 0);
+                                error_class = Some(ErrorClass::Write);
                                 collect_log_string.push('\n');
-                                collect_log_string.push_str(err.as_str());
+                                collect_log_string.push_str(err.message.as_str());
                             }
                         };
+                        publish_progress(state, log_id, 1, &metrics);
                     }
                     Err(err) => {
+                        status = 3;
+                        error_class = Some(ErrorClass::Process);
                         res_data_str = err.to_string();
                         collect_log_string.push_str(res_data_str.as_str());
                     }
                 }
-    
-                if let Some(err) = CollectLogService::update_by_id(
-                    &state.conn,
+
+                if let Some(err) = CollectLogService::update_and_publish(
+                    state,
                     log_id,
                     collect_log::Model {
-                        status: 2,
-                        running_log: collect_log_string,
-                        ..Default::default()
+                        partial,
+                        error_class: error_class.map(|x| x.as_str().to_string()),
+                        ..metrics_log(status, collect_log_string, &metrics)
                     },
                 )
                 .await
@@ -848,13 +1169,12 @@ pub async fn process_data(
             Err(err) => {
                 let log = anyhow!("{}", err);
                 debug!("{}", log);
-                if let Some(err) = CollectLogService::update_by_id(
-                    &state.conn,
+                if let Some(err) = CollectLogService::update_and_publish(
+                    state,
                     log_id,
                     collect_log::Model {
-                        status: 3,
-                        running_log: log.to_string(),
-                        ..Default::default()
+                        error_class: Some(ErrorClass::Source.as_str().to_string()),
+                        ..metrics_log(3, log.to_string(), &metrics)
                     },
                 )
                 .await
@@ -862,92 +1182,159 @@ pub async fn process_data(
                 {
                     error!("status: 3 运行完毕；日志更新失败: {err}");
                 };
-    
+
                 Err(log)
             }
         }
     }
-
 }
 
-pub async fn collect_data_with_http(
+/// 分页配置了concurrency且返回数据中有总数时，在第一页之后同时请求多页，按页码顺序合并结果；
+/// 不知道总页数时逐页请求，避免请求超出最后一页；
+/// 返回是否还有下一页、处理后的数据与本次请求的页数，实际发出的请求数（包括重试）累加到metrics中
+async fn collect_pages(
+    state: &AppState,
     data: &Model,
-    body: Option<String>,
-) -> anyhow::Result<(bool, anyhow::Result<Vec<String>>)> {
-    let mut http = process_core::http::Http::new();
-    let mut headers = None;
+    template_context: &mut TemplateContext,
+    paginator: Option<&mut Paginator>,
+    max_count: i32,
+    log_id: Option<i32>,
+    metrics: &mut RunMetrics,
+) -> anyhow::Result<(bool, anyhow::Result<Vec<String>>, i32)> {
+    let request_counter = Arc::new(AtomicUsize::new(0));
+    let counter = Some(request_counter.clone());
+    let res = async {
+        let paginator = match paginator {
+            Some(x) if x.request_count() > 0 && x.concurrency() > 1 && x.remaining().is_some() => x,
+            paginator => {
+                let (has_next_page, res, bytes) = collect_data_with_http(
+                    state,
+                    data,
+                    template_context,
+                    paginator,
+                    log_id,
+                    counter,
+                )
+                .await?;
+                metrics.bytes += bytes as i64;
+                return Ok((has_next_page, res, 1));
+            }
+        };
 
-    let get_map_rules = |value: Option<&serde_json::Value>| {
-        // [["a", "b"]]
-        if let Some(rules) = value {
-            return rules
-                .as_array()
-                .unwrap()
-                .iter()
-                .map(|x| {
-                    [
-                        x[0].as_str().unwrap().to_string(),
-                        x[1].as_str().unwrap().to_string(),
-                    ]
-                })
-                .collect();
+        let mut batch = (paginator.concurrency() as i64).min(max_count.max(1) as i64);
+        if let Some(remaining) = paginator.remaining() {
+            batch = batch.min(remaining.max(1));
+        }
+        let start = paginator.request_count();
+        let pages = (start..start + batch).map(|index| {
+            let mut page = paginator.at_page(index);
+            let mut context = template_context.clone();
+            context.loop_counts = Some(index as i32);
+            let counter = counter.clone();
+            async move {
+                let res = collect_data_with_http(
+                    state,
+                    data,
+                    &mut context,
+                    Some(&mut page),
+                    log_id,
+                    counter,
+                )
+                .await;
+                (res, page, context)
+            }
+        });
+        let results = stream::iter(pages)
+            .buffered(batch as usize)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut data_res = vec![];
+        let mut request_count = 0;
+        for (res, page, context) in results {
+            let (has_next_page, res, bytes) = res?;
+            request_count += 1;
+            metrics.bytes += bytes as i64;
+            *paginator = page;
+            template_context.prev_response = context.prev_response;
+            match res {
+                Ok(x) => data_res.extend(x),
+                Err(err) => return Ok((false, Err(err), request_count)),
+            }
+            if !has_next_page {
+                return Ok((false, Ok(data_res), request_count));
+            }
         }
-        vec![]
-    };
 
-    if let Some(h) = &data.headers {
-        let temp = h
-            .as_object()
-            .unwrap()
-            .iter()
-            .map(|(key, value)| (key.clone(), value.as_str().unwrap().to_string()))
-            .collect::<Vec<(String, String)>>();
-        headers = Some(temp)
+        Ok((true, Ok(data_res), request_count))
     }
+    .await;
+    metrics.request_count += request_counter.load(Ordering::Relaxed) as i64;
 
-    let mut http_receive = http
-        .receive(
-            data.url.clone(),
-            HttpConfig {
-                method: data.method.clone().parse().unwrap(),
-                headers,
-                body,
-            },
-        )
-        .await?;
+    res
+}
+
+/// 请求一页数据，返回是否还有下一页、处理后的数据与接口返回内容的字节数
+/// 传入request_counter时统计实际发出的请求数
+pub async fn collect_data_with_http(
+    state: &AppState,
+    data: &Model,
+    template_context: &mut TemplateContext,
+    paginator: Option<&mut Paginator>,
+    log_id: Option<i32>,
+    request_counter: Option<Arc<AtomicUsize>>,
+) -> anyhow::Result<(bool, anyhow::Result<Vec<String>>, usize)> {
+    let mut received = receive_http_data(
+        state,
+        data,
+        template_context,
+        paginator.as_deref(),
+        log_id,
+        request_counter,
+    )
+    .await?;
+    let bytes = received.http.response_bytes;
+
+    let paginated_next = match paginator {
+        Some(paginator) => Some(paginator.advance(
+            &received.url,
+            &received.http.data,
+            &received.http.response_headers,
+        )?),
+        None => None,
+    };
 
     let mut has_next_page = true;
 
     if let Some(filed_of_result_data) = data.filed_of_result_data.as_ref() {
-        if let Ok(found_data) = find_value(filed_of_result_data.borrow(), &http_receive.data, false)
+        if let Ok(found_data) =
+            find_value(filed_of_result_data.borrow(), &received.http.data, false)
         {
             if let Some(array) = found_data.as_array() {
                 if array.is_empty() {
                     has_next_page = false;
-                    return Ok((has_next_page, Ok(vec![])));
+                    return Ok((has_next_page, Ok(vec![]), bytes));
                 }
             } else {
                 has_next_page = false;
-                return Ok((has_next_page, Ok(vec![])));
+                return Ok((has_next_page, Ok(vec![]), bytes));
             }
         } else {
             has_next_page = false;
-            return Ok((has_next_page, Ok(vec![])));
+            return Ok((has_next_page, Ok(vec![]), bytes));
         }
     } else {
         has_next_page = false;
     }
 
-    if let Some(x) = &data.nested_config {
-        let config: Vec<NestedConfig> = serde_json::from_value(x.clone()).unwrap();
-        http_receive.set_nested_config(config);
+    if let Some(x) = paginated_next {
+        has_next_page = x;
     }
 
-    if let Some(x) = &data.map_rules {
-        if !x.as_array().unwrap().is_empty() {
-            http_receive.set_map_rules(get_map_rules(Some(x)));
-        }
-    }
+    received.run_after_steps(template_context).await?;
+    let mut http_receive = received.http;
+
+    set_process_rules(data, &mut http_receive)?;
 
     let res = http_receive
         .serde()?
@@ -955,7 +1342,378 @@ pub async fn collect_data_with_http(
         .export()
         .await;
 
-    Ok((has_next_page, res))
+    Ok((has_next_page, res, bytes))
+}
+
+/// 预览采集配置每个阶段处理后的数据
+#[derive(Debug, Default, Serialize, TS)]
+#[ts(
+    export,
+    export_to = "ui/api/models/auto-generates/CollectConfigPreview.ts",
+    rename = "CollectConfigPreview"
+)]
+pub struct PreviewResult {
+    /// 接口返回或者传入的示例数据
+    #[ts(type = "any")]
+    pub raw: Option<serde_json::Value>,
+    /// 按nested_config展开后的数据
+    #[ts(type = "any")]
+    pub nested: Option<serde_json::Value>,
+    /// 按map_rules映射后的数据
+    #[ts(type = "any")]
+    pub mapped: Option<serde_json::Value>,
+    /// 按mask_rules脱敏后的数据
+    #[ts(type = "any")]
+    pub masked: Option<serde_json::Value>,
+    pub sql: Option<Vec<String>>,
+    /// map_rules中每条规则的匹配情况
+    #[ts(type = "Array<any>")]
+    pub diagnostics: Vec<MapRuleDiagnostic>,
+    /// 处理中断时的错误信息，之前阶段的数据仍会返回
+    pub error: Option<String>,
+}
+
+/// 使用未保存的采集配置预览每个阶段的数据，不会写入缓存表与采集日志
+pub async fn preview(state: &AppState, params: PreviewParams) -> PreviewResult {
+    let mut result = PreviewResult::default();
+    if let Err(err) = preview_stages(state, params, &mut result).await {
+        result.error = Some(err.to_string());
+    }
+
+    result
+}
+
+async fn preview_stages(
+    state: &AppState,
+    params: PreviewParams,
+    result: &mut PreviewResult,
+) -> anyhow::Result<()> {
+    let mut data = params.config;
+    if let Some(id) = params.id {
+        data.id = id;
+        if let Some(connection_config) = data.connection_config.take() {
+            let db_data = CollectConfigService::find_by_id(&state.conn, id).await?;
+            data.connection_config = Some(encrypt_json_secrets(
+                connection_config,
+                db_data.connection_config.as_ref(),
+                &CONNECTION_SECRET_FIELDS,
+            )?);
+        }
+    }
+    let data = &data;
+
+    let raw = match params.sample {
+        Some(serde_json::Value::String(text)) => {
+            let response_config: ResponseConfig = match &data.response_config {
+                Some(x) => serde_json::from_value(x.clone())
+                    .map_err(|err| anyhow!("response_config 无法解析: {err}"))?,
+                None => ResponseConfig::default(),
+            };
+            decode_response(&text, None, &response_config)?
+        }
+        Some(x) => x,
+        None => {
+            let mut template_context = TemplateContext {
+                loop_counts: Some(0),
+                watermark: CollectLogService::find_last_success_time(&state.conn, data.id).await?,
+                body: data
+                    .body
+                    .as_deref()
+                    .and_then(|x| serde_json::from_str(x).ok()),
+                ..Default::default()
+            };
+            let paginator = match &data.pagination_config {
+                Some(x) => Some(Paginator::new(
+                    serde_json::from_value::<PaginationConfig>(x.clone())
+                        .map_err(|err| anyhow!("pagination_config 无法解析: {err}"))?,
+                )),
+                None => None,
+            };
+            let mut received = receive_http_data(
+                state,
+                data,
+                &mut template_context,
+                paginator.as_ref(),
+                None,
+                None,
+            )
+            .await?;
+            received.run_after_steps(&template_context).await?;
+            received.http.data
+        }
+    };
+    result.raw = Some(raw.clone());
+
+    let mut http = Http::new();
+    set_process_rules(data, &mut http)?;
+
+    let mut value = raw;
+    for config in http.nested_config.iter().flatten() {
+        value = flat_nested_object_by_config(&value, config)?;
+    }
+    result.nested = Some(value.clone());
+
+    if let Some(map_rules) = &http.map_rules {
+        result.diagnostics = check_map_rules(&value, map_rules);
+        value = map_data(&value, map_rules)?;
+    }
+    result.mapped = Some(value.clone());
+
+    if let Some(mask_rules) = &http.mask_rules {
+        mask_data(&mut value, mask_rules);
+    }
+    result.masked = Some(value.clone());
+
+    if !data.template_string.trim().is_empty() {
+        let sql = http
+            .set_data(value)
+            .set_template_string(data.template_string.clone())
+            .export()
+            .await?;
+        result.sql = Some(sql);
+    }
+
+    Ok(())
+}
+
+/// 主请求返回的数据，处理数据前需要先执行after采集步骤
+struct ReceivedData {
+    http: Http,
+    /// 主请求的地址，分页时用于解析Link请求头中的相对地址
+    url: String,
+    steps: Vec<HttpStep>,
+    step_config: HttpConfig,
+}
+
+impl ReceivedData {
+    async fn run_after_steps(&mut self, template_context: &TemplateContext) -> anyhow::Result<()> {
+        let context = step_template_context(template_context, template_context.steps.as_ref());
+        for step in self.steps.iter().filter(|x| x.stage == StepStage::After) {
+            self.http
+                .run_step(step, &self.step_config, |template, item| {
+                    render_step_template(&context, template, item)
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 执行before采集步骤与主请求
+async fn receive_http_data(
+    state: &AppState,
+    data: &Model,
+    template_context: &mut TemplateContext,
+    paginator: Option<&Paginator>,
+    log_id: Option<i32>,
+    request_counter: Option<Arc<AtomicUsize>>,
+) -> anyhow::Result<ReceivedData> {
+    let mut http = Http::new();
+    let mut headers = None;
+
+    let response_config: ResponseConfig = match &data.response_config {
+        Some(x) => serde_json::from_value(x.clone())
+            .map_err(|err| anyhow!("response_config 无法解析: {err}"))?,
+        None => ResponseConfig::default(),
+    };
+
+    let options: HttpOptions = match &data.http_options {
+        Some(x) => serde_json::from_value(x.clone())
+            .map_err(|err| anyhow!("http_options 无法解析: {err}"))?,
+        None => HttpOptions::default(),
+    };
+
+    let assertion: ResponseAssertion = match &data.response_assertion {
+        Some(x) => serde_json::from_value(x.clone())
+            .map_err(|err| anyhow!("response_assertion 无法解析: {err}"))?,
+        None => ResponseAssertion::default(),
+    };
+
+    let auth = match data.auth_profile_id {
+        Some(id) => Some(AuthProfileService::get_provider(state, id).await?),
+        None => None,
+    };
+
+    // 只有正式执行的任务才保存原始请求与返回
+    let capture_config = match (&data.capture_config, log_id) {
+        (Some(x), Some(_)) => Some(parse_capture_config(x)?),
+        _ => None,
+    };
+
+    let connection = match (&data.connection_config, data.auth_profile_id) {
+        (Some(x), _) => parse_connection_config(x)?,
+        (None, Some(id)) => AuthProfileService::get_connection_config(&state.conn, id)
+            .await?
+            .unwrap_or_default(),
+        (None, None) => ConnectionConfig::default(),
+    };
+
+    let steps: Vec<HttpStep> = match &data.steps {
+        Some(x) => {
+            serde_json::from_value(x.clone()).map_err(|err| anyhow!("steps 无法解析: {err}"))?
+        }
+        None => vec![],
+    };
+    // 采集步骤共用认证、超时重试与返回校验配置
+    let step_config = HttpConfig {
+        auth: auth.clone(),
+        options: options.clone(),
+        assertion: assertion.clone(),
+        connection: connection.clone(),
+        rate_limiters: Some(state.rate_limiters.clone()),
+        rate_limit_name: Some(format!("collect_config:{}", data.id)),
+        request_counter,
+        ..Default::default()
+    };
+
+    let before_steps = steps
+        .iter()
+        .filter(|x| x.stage == StepStage::Before)
+        .collect::<Vec<&HttpStep>>();
+    if !before_steps.is_empty() {
+        let mut step_results = Http::new();
+        step_results.set_data(json!({}));
+        for step in before_steps {
+            let context = step_template_context(template_context, Some(&step_results.data));
+            step_results
+                .run_step(step, &step_config, |template, item| {
+                    render_step_template(&context, template, item)
+                })
+                .await?;
+        }
+        template_context.steps = Some(step_results.data);
+    }
+
+    if let Some(h) = &data.headers {
+        let mut temp = vec![];
+        for (key, value) in h.as_object().unwrap() {
+            let value = render_template(value.as_str().unwrap_or_default(), template_context)?;
+            temp.push((key.clone(), value));
+        }
+        headers = Some(temp)
+    }
+
+    let url = render_template(&data.url, template_context)?;
+    let body = match &data.body {
+        Some(x) => Some(render_template(x, template_context)?),
+        None => None,
+    };
+
+    let (url, body) = match &paginator {
+        Some(paginator) => paginator.apply(&url, body.as_deref())?,
+        None => (url, body),
+    };
+
+    let res = http
+        .receive(
+            url.clone(),
+            HttpConfig {
+                method: data.method.clone().parse().unwrap(),
+                headers,
+                body,
+                response_config,
+                auth,
+                options,
+                assertion,
+                connection,
+                rate_limiters: step_config.rate_limiters.clone(),
+                rate_limit_name: step_config.rate_limit_name.clone(),
+                capture_limit: capture_config.as_ref().map(|x| x.max_body_size),
+                request_counter: step_config.request_counter.clone(),
+            },
+        )
+        .await;
+    if let (Some(config), Some(log_id), Some(capture)) =
+        (&capture_config, log_id, http.capture.take())
+    {
+        if let Err(err) =
+            HttpCaptureService::save_capture(&state.conn, data.id, log_id, capture, config).await
+        {
+            error!("原始请求与返回保存失败: {err}");
+        }
+    }
+    let http = res?;
+
+    template_context.prev_response = Some(http.data.clone());
+
+    Ok(ReceivedData {
+        http,
+        url,
+        steps,
+        step_config,
+    })
+}
+
+/// 按采集配置设置返回数据的展开、映射与脱敏规则
+pub fn set_process_rules(data: &Model, http: &mut Http) -> anyhow::Result<()> {
+    let get_map_rules = |value: Option<&serde_json::Value>| {
+        // [["a", "b"]]
+        if let Some(rules) = value {
+            return rules
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|x| {
+                    [
+                        x[0].as_str().unwrap().to_string(),
+                        x[1].as_str().unwrap().to_string(),
+                    ]
+                })
+                .collect();
+        }
+        vec![]
+    };
+
+    if let Some(x) = &data.nested_config {
+        let config: Vec<NestedConfig> = serde_json::from_value(x.clone())
+            .map_err(|err| anyhow!("nested_config 无法解析: {err}"))?;
+        http.set_nested_config(config);
+    }
+
+    if let Some(x) = &data.map_rules {
+        if !x.as_array().unwrap().is_empty() {
+            http.set_map_rules(get_map_rules(Some(x)));
+        }
+    }
+
+    if let Some(x) = &data.mask_rules {
+        http.set_mask_rules(parse_mask_rules(x)?);
+    }
+
+    Ok(())
+}
+
+/// 采集步骤使用的模板变量，不包含数据量较大的上一次请求返回的数据
+fn step_template_context(
+    context: &TemplateContext,
+    steps: Option<&serde_json::Value>,
+) -> TemplateContext {
+    TemplateContext {
+        loop_counts: context.loop_counts,
+        watermark: context.watermark,
+        prev_response: None,
+        body: context.body.clone(),
+        steps: steps.cloned(),
+        item: None,
+    }
+}
+
+fn render_step_template(
+    context: &TemplateContext,
+    template: &str,
+    item: Option<&serde_json::Value>,
+) -> anyhow::Result<String> {
+    match item {
+        Some(item) => render_template(
+            template,
+            &TemplateContext {
+                item: Some(item.clone()),
+                ..context.clone()
+            },
+        ),
+        None => render_template(template, context),
+    }
 }
 
 async fn update_job_scheduler(
@@ -963,7 +1721,11 @@ async fn update_job_scheduler(
     data: &Model,
     db_data: &Model,
 ) -> Result<Option<Uuid>, JobSchedulerError> {
-    if db_data.cron != data.cron {
+    // 暂停中的任务恢复时再添加
+    if !db_data.enabled {
+        return Ok(None);
+    }
+    if db_data.cron != data.cron || db_data.timezone != data.timezone {
         if let Some(job_id) = db_data.job_id {
             state.sched.remove(&job_id).await?;
         }
@@ -978,24 +1740,50 @@ async fn create_job_scheduler(
 ) -> Result<Option<Uuid>, JobSchedulerError> {
     if let Some(cron) = data.cron.as_ref() {
         let sched_state = state.clone();
-        let data = data.clone();
+        let id = data.id;
+        let job_cron = cron.clone();
+        let timezone = data.timezone.clone();
         let job_id = state
             .sched
-            .add(Job::new_async_tz(
-                format_cron(cron.clone()).as_str(),
-                Local::now().timezone(),
+            .add(ScheduleService::cron_job(
+                cron,
+                data.timezone.as_deref(),
                 move |_uuid, _l| {
                     let st = sched_state.clone();
-                    let item_c = data.clone();
+                    let cron = job_cron.clone();
+                    let timezone = timezone.clone();
                     Box::pin(async move {
-                        let task_id = Uuid::new_v4().simple();
-                        let stc = st.clone();
-                        tokio::spawn(async move {
-                            CollectConfigService::execute_task(&stc, &item_c, task_id).await;
-                        });
-                        let mut task = st.log_task.write().await;
-                        task.insert(task_id, LogTask::new());
-                        // CollectConfigService::execute_task(&st, &item_c, t_id).await
+                        // 其他节点修改、暂停或者删除了配置时，以数据库中的为准
+                        let item = match CollectConfigService::find_by_id(&st.conn, id).await {
+                            Ok(item)
+                                if item.enabled
+                                    && item.cron.as_ref() == Some(&cron)
+                                    && item.timezone == timezone =>
+                            {
+                                item
+                            }
+                            _ => return,
+                        };
+                        if !ScheduleService::claim_fire(
+                            &st.conn,
+                            TaskKind::Collect,
+                            id,
+                            &cron,
+                            timezone.as_deref(),
+                            &st.node_id,
+                        )
+                        .await
+                        {
+                            return;
+                        }
+
+                        match CollectConfigService::enqueue_task(&st, &item, None, None).await {
+                            Ok(Some(_)) => {}
+                            Ok(None) => {
+                                warn!("采集配置：{} 上一次任务仍在执行，本次跳过", item.name)
+                            }
+                            Err(err) => error!("采集配置：{} 任务提交失败 {err}", item.name),
+                        }
                     })
                 },
             )?)
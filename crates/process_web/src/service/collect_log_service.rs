@@ -4,8 +4,11 @@ use sea_orm::ActiveValue::{Set, Unchanged};
 use sea_orm::*;
 use tracing::debug;
 
+use crate::api::common::AppState;
 use crate::entity::collect_config;
 use crate::entity::collect_log;
+use crate::service::run_event_service::{EventLevel, RunEventService, RunTiming};
+use crate::service::task_executor_service::TaskKind;
 
 pub struct CollectLogService;
 
@@ -83,6 +86,21 @@ impl CollectLogService {
         CollectLogService::save(db, Some(id), data).await
     }
 
+    /// 更新执行中的日志，同时推送给实时查看日志的客户端
+    pub async fn update_and_publish(
+        state: &AppState,
+        id: i32,
+        data: collect_log::Model,
+    ) -> Result<collect_log::Model, DbErr> {
+        let (status, message) = (data.status, data.running_log.clone());
+        let res = Self::update_by_id(&state.conn, id, data).await?;
+        state
+            .run_updates
+            .publish_log(TaskKind::Collect, id, status, &message);
+
+        Ok(res)
+    }
+
     pub async fn save(
         db: &DbConn,
         id: Option<i32>,
@@ -96,8 +114,41 @@ impl CollectLogService {
         if data.task_id.is_some() {
             active_data.task_id = Set(data.task_id);
         }
+        if data.run_id.is_some() {
+            active_data.run_id = Set(data.run_id);
+        }
+        if data.node_id.is_some() {
+            active_data.node_id = Set(data.node_id);
+        }
+        if data.attempt.is_some() {
+            active_data.attempt = Set(data.attempt);
+            active_data.max_attempts = Set(data.max_attempts);
+            active_data.retry_of = Set(data.retry_of);
+        }
+        if data.partial.is_some() {
+            active_data.partial = Set(data.partial);
+        }
+        if data.request_count.is_some() {
+            active_data.request_count = Set(data.request_count);
+        }
+        if data.rows_fetched.is_some() {
+            active_data.rows_fetched = Set(data.rows_fetched);
+        }
+        if data.rows_written.is_some() {
+            active_data.rows_written = Set(data.rows_written);
+        }
+        if data.rows_rejected.is_some() {
+            active_data.rows_rejected = Set(data.rows_rejected);
+        }
+        if data.bytes.is_some() {
+            active_data.bytes = Set(data.bytes);
+        }
+        if data.error_class.is_some() {
+            active_data.error_class = Set(data.error_class);
+        }
+        let message = data.running_log.trim();
 
-        if let Some(id) = id {
+        let model = if let Some(id) = id {
             let db_data = collect_log::Entity::find_by_id(id)
                 .one(db)
                 .await?
@@ -105,19 +156,45 @@ impl CollectLogService {
 
             active_data.id = Unchanged(db_data.id);
             active_data.status = Set(data.status);
-            let log = format!("{}\n{}", db_data.running_log, data.running_log);
-            active_data.running_log = Set(log);
+            // 只保存最近的一条，完整的执行过程保存在run_event中
+            if !message.is_empty() {
+                active_data.running_log = Set(message.to_string());
+            }
+            let timing = RunTiming::on_status(data.status, db_data.started_at, now);
+            if timing.started_at.is_some() {
+                active_data.started_at = Set(timing.started_at);
+            }
+            if timing.finished_at.is_some() {
+                active_data.finished_at = Set(timing.finished_at);
+                active_data.duration_ms = Set(timing.duration_ms);
+            }
             active_data.update_time = Set(now);
 
-            active_data.update(db).await
+            active_data.update(db).await?
         } else {
+            let timing = RunTiming::on_status(data.status, None, now);
             active_data.collect_config_id = Set(data.collect_config_id);
-            active_data.status = Set(0);
-            active_data.running_log = Set(data.running_log);
+            active_data.status = Set(data.status);
+            active_data.running_log = Set(message.to_string());
+            active_data.started_at = Set(timing.started_at);
+            active_data.finished_at = Set(timing.finished_at);
             active_data.create_time = Set(now);
             active_data.update_time = Set(now);
-            active_data.insert(db).await
+            active_data.insert(db).await?
+        };
+
+        if !message.is_empty() {
+            RunEventService::add(
+                db,
+                TaskKind::Collect,
+                model.id,
+                EventLevel::from_status(data.status),
+                message,
+            )
+            .await?;
         }
+
+        Ok(model)
     }
 
     pub async fn delete(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
@@ -127,6 +204,22 @@ impl CollectLogService {
             .ok_or(DbErr::Custom("Cannot find data by id.".to_owned()))
             .map(Into::into)?;
 
+        RunEventService::delete_by_log(db, TaskKind::Collect, id).await?;
         collect_log.delete(db).await
     }
+
+    /// 采集任务上一次运行成功的开始时间，用作增量采集的水位线
+    pub async fn find_last_success_time(
+        db: &DbConn,
+        collect_config_id: i32,
+    ) -> Result<Option<prelude::DateTime>, DbErr> {
+        let log = collect_log::Entity::find()
+            .filter(collect_log::Column::CollectConfigId.eq(collect_config_id))
+            .filter(collect_log::Column::Status.eq(2))
+            .order_by_desc(collect_log::Column::Id)
+            .one(db)
+            .await?;
+
+        Ok(log.map(|x| x.create_time))
+    }
 }
@@ -1,4 +1,5 @@
 use process_core::db::DataSource;
+use process_core::mask::{mask_data, select_mask_rules};
 use sea_orm::ActiveValue::{Set, Unchanged};
 use sea_orm::*;
 use tracing::debug;
@@ -7,6 +8,7 @@ use crate::api::data_sharing_config::ListParams;
 use crate::entity::data_sharing_config;
 use crate::entity::data_sharing_config::Model;
 use crate::service::data_source_list_service::DataSourceListService;
+use crate::utils::parse_mask_profiles;
 
 pub struct DataSharingConfigService;
 
@@ -59,6 +61,9 @@ impl DataSharingConfigService {
     }
 
     pub async fn save(db: &DbConn, id: Option<i32>, data: Model) -> Result<Model, DbErr> {
+        if let Some(x) = data.mask_rules.as_ref() {
+            parse_mask_profiles(x).map_err(|err| DbErr::Custom(err.to_string()))?;
+        }
         debug!("data: {:?}, id: {:?}", data, id);
         let now = chrono::Local::now().naive_local();
         let mut active_data = data_sharing_config::ActiveModel {
@@ -66,6 +71,7 @@ impl DataSharingConfigService {
             table_name: Set(data.table_name),
             query_sql: Set(data.query_sql),
             data_source_id: Set(data.data_source_id),
+            mask_rules: Set(data.mask_rules),
             ..Default::default()
         };
         if let Some(id) = id {
@@ -104,9 +110,11 @@ impl DataSharingConfigService {
     }
 
     // TODO 增加授权认证
+    /// auth_id 为调用方身份，用于选择对应的脱敏规则
     pub async fn get_data(
         db: &DbConn,
         api_id: String,
+        auth_id: &str,
         payload: Option<serde_json::Value>,
     ) -> Result<Vec<serde_json::Value>, DbErr> {
         let data = Self::find_by_api_id(db, api_id).await?;
@@ -134,11 +142,23 @@ impl DataSharingConfigService {
         let data_source = DataSourceListService::find_by_id(db, data.data_source_id).await?;
 
         let data_source: DataSource = data_source.into();
-        process_core::db::find_all_sql(&data_source, query_sql)
+        let mut list = process_core::db::find_all_sql(&data_source, query_sql)
             .await
             .map_err(|err| {
                 let s = format!("{}", err);
                 DbErr::Custom(s.to_owned())
-            })
+            })?;
+
+        if let Some(mask_rules) = data.mask_rules.as_ref() {
+            let profiles =
+                parse_mask_profiles(mask_rules).map_err(|err| DbErr::Custom(err.to_string()))?;
+            if let Some(rules) = select_mask_rules(&profiles, auth_id) {
+                for item in list.iter_mut() {
+                    mask_data(item, rules);
+                }
+            }
+        }
+
+        Ok(list)
     }
 }
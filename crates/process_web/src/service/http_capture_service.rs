@@ -0,0 +1,181 @@
+use anyhow::anyhow;
+use process_core::http::{decode_response, Http, HttpCapture, ResponseConfig};
+use process_core::process::{Export, Serde};
+use sea_orm::ActiveValue::Set;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::api::http_capture::ListParams;
+use crate::entity::http_capture::Model;
+use crate::entity::{collect_config, http_capture};
+use crate::service::collect_config_service::{set_process_rules, CollectConfigService};
+
+/// 原始请求与返回的保存配置
+/// ```json
+/// {"max_body_size": 1048576, "max_captures": 20, "retention_days": 7}
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CaptureConfig {
+    /// 返回内容最多保存的字节数，超出部分被截断
+    pub max_body_size: usize,
+    /// 每次执行最多保存的请求数
+    pub max_captures: u64,
+    /// 保存的天数
+    pub retention_days: i64,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            max_body_size: 1024 * 1024,
+            max_captures: 20,
+            retention_days: 7,
+        }
+    }
+}
+
+pub fn parse_capture_config(value: &Value) -> anyhow::Result<CaptureConfig> {
+    serde_json::from_value(value.clone()).map_err(|err| anyhow!("capture_config 无法解析: {err}"))
+}
+
+pub struct HttpCaptureService;
+
+impl HttpCaptureService {
+    pub async fn find_by_id(db: &DbConn, id: i32) -> Result<Model, DbErr> {
+        http_capture::Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find data by id.".to_owned()))
+    }
+
+    pub async fn list(
+        db: &DbConn,
+        page: u64,
+        page_size: u64,
+        data: Option<ListParams>,
+    ) -> Result<(Vec<Model>, u64), DbErr> {
+        let mut conditions = Condition::all();
+        if let Some(data) = data {
+            if let Some(id) = data.collect_config_id {
+                conditions = conditions.add(http_capture::Column::CollectConfigId.eq(id));
+            }
+            if let Some(id) = data.collect_log_id {
+                conditions = conditions.add(http_capture::Column::CollectLogId.eq(id));
+            }
+        }
+
+        let paginator = http_capture::Entity::find()
+            .filter(conditions)
+            .order_by_desc(http_capture::Column::Id)
+            .paginate(db, page_size);
+
+        let num_pages = paginator.num_items().await?;
+
+        paginator.fetch_page(page - 1).await.map(|p| (p, num_pages))
+    }
+
+    pub async fn delete(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+        http_capture::Entity::delete_by_id(id).exec(db).await
+    }
+
+    /// 保存一次请求的原始请求与返回，超过max_captures时不再保存
+    pub async fn save_capture(
+        db: &DbConn,
+        collect_config_id: i32,
+        collect_log_id: i32,
+        capture: HttpCapture,
+        config: &CaptureConfig,
+    ) -> Result<(), DbErr> {
+        let count = http_capture::Entity::find()
+            .filter(http_capture::Column::CollectLogId.eq(collect_log_id))
+            .count(db)
+            .await?;
+        if count >= config.max_captures {
+            return Ok(());
+        }
+
+        http_capture::ActiveModel {
+            collect_config_id: Set(collect_config_id),
+            collect_log_id: Set(Some(collect_log_id)),
+            method: Set(capture.method),
+            url: Set(capture.url),
+            request_headers: Set(json!(capture.request_headers)),
+            request_body: Set(capture.request_body),
+            status: Set(capture.status as i32),
+            response_headers: Set(json!(capture.response_headers)),
+            response_body: Set(capture.response_body),
+            truncated: Set(capture.truncated),
+            create_time: Set(chrono::Local::now().naive_local()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 删除超过保存天数的数据
+    pub async fn cleanup(
+        db: &DbConn,
+        collect_config_id: i32,
+        retention_days: i64,
+    ) -> Result<u64, DbErr> {
+        let time = chrono::Local::now().naive_local() - chrono::Duration::days(retention_days);
+        let res = http_capture::Entity::delete_many()
+            .filter(http_capture::Column::CollectConfigId.eq(collect_config_id))
+            .filter(http_capture::Column::CreateTime.lt(time))
+            .exec(db)
+            .await?;
+
+        Ok(res.rows_affected)
+    }
+
+    /// 使用保存的返回数据重新执行映射、脱敏与导出，不会请求上游接口，也不会写入数据；
+    /// 未传入采集配置时使用数据库中当前的采集配置，返回处理后的数据与导出的SQL
+    pub async fn replay(
+        db: &DbConn,
+        id: i32,
+        config: Option<collect_config::Model>,
+    ) -> anyhow::Result<Value> {
+        let capture = HttpCaptureService::find_by_id(db, id).await?;
+        if capture.truncated {
+            return Err(anyhow!("返回内容保存时被截断，无法重放"));
+        }
+        let config = match config {
+            Some(x) => x,
+            None => CollectConfigService::find_by_id(db, capture.collect_config_id).await?,
+        };
+
+        let response_config: ResponseConfig = match &config.response_config {
+            Some(x) => serde_json::from_value(x.clone())
+                .map_err(|err| anyhow!("response_config 无法解析: {err}"))?,
+            None => ResponseConfig::default(),
+        };
+        let response_headers: Vec<(String, String)> =
+            serde_json::from_value(capture.response_headers.clone()).unwrap_or_default();
+        let content_type = HttpCapture {
+            response_headers,
+            ..Default::default()
+        }
+        .content_type()
+        .map(|x| x.to_string());
+
+        let mut http = Http::new();
+        http.set_data(decode_response(
+            &capture.response_body,
+            content_type.as_deref(),
+            &response_config,
+        )?);
+        set_process_rules(&config, &mut http)?;
+        let mut http = http.serde()?;
+        let data = http.data.clone();
+        let sql = http
+            .set_template_string(config.template_string.clone())
+            .export()
+            .await?;
+
+        Ok(json!({"data": data, "sql": sql}))
+    }
+}
@@ -1,21 +1,29 @@
-use sea_orm::{ColumnTrait, DbConn, DbErr, EntityTrait, QueryFilter};
+use sea_orm::{ColumnTrait, Condition, DbConn, DbErr, EntityTrait, QueryFilter};
 
 use crate::entity::{collect_log, sync_log};
 
+use super::run_event_service::ErrorClass;
 use super::{collect_log_service::CollectLogService, sync_log_service::SyncLogService};
 
 pub struct LogService;
 
 impl LogService {
-    /// 查看符合条件的日志将其状态修改
+    /// 查看符合条件的日志将其状态修改，未记录节点的日志视为当前节点的
     pub async fn reset_log_status(
         db: &DbConn,
+        node_id: &str,
         pre_status: i32,
         status: i32,
         msg: &str,
+        error_class: ErrorClass,
     ) -> Result<bool, DbErr> {
         let log1 = collect_log::Entity::find()
             .filter(collect_log::Column::Status.eq(pre_status))
+            .filter(
+                Condition::any()
+                    .add(collect_log::Column::NodeId.is_null())
+                    .add(collect_log::Column::NodeId.eq(node_id)),
+            )
             .all(db)
             .await?;
 
@@ -26,6 +34,7 @@ impl LogService {
                 collect_log::Model {
                     status,
                     running_log: msg.to_string(),
+                    error_class: Some(error_class.as_str().to_string()),
                     ..Default::default()
                 },
             )
@@ -34,6 +43,11 @@ impl LogService {
 
         let log2 = sync_log::Entity::find()
             .filter(sync_log::Column::Status.eq(pre_status))
+            .filter(
+                Condition::any()
+                    .add(sync_log::Column::NodeId.is_null())
+                    .add(sync_log::Column::NodeId.eq(node_id)),
+            )
             .all(db)
             .await?;
 
@@ -44,6 +58,7 @@ impl LogService {
                 sync_log::Model {
                     status,
                     running_log: msg.to_string(),
+                    error_class: Some(error_class.as_str().to_string()),
                     ..Default::default()
                 },
             )
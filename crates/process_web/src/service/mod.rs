@@ -1,9 +1,16 @@
+pub mod auth_profile_service;
 pub mod collect_config_service;
 pub mod collect_log_service;
 pub mod data_sharing_config_service;
 pub mod data_source_list_service;
+pub mod http_capture_service;
 pub mod log_service;
+pub mod run_event_service;
+pub mod schedule_service;
 pub mod sharing_request_log_service;
 pub mod sync_config_service;
 pub mod sync_log_service;
 pub mod table_service;
+pub mod task_dependency_service;
+pub mod task_executor_service;
+pub mod task_retry_service;
@@ -0,0 +1,348 @@
+use chrono::NaiveDateTime;
+use sea_orm::ActiveValue::Set;
+use sea_orm::*;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use ts_rs::TS;
+
+use crate::entity::{collect_log, run_event, sync_log};
+use crate::service::task_executor_service::TaskKind;
+
+/// 事件的级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+impl EventLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventLevel::Info => "info",
+            EventLevel::Warn => "warn",
+            EventLevel::Error => "error",
+        }
+    }
+
+    /// 按日志状态确定事件级别，3失败为error，5中断为warn
+    pub fn from_status(status: i32) -> Self {
+        match status {
+            3 => EventLevel::Error,
+            5 => EventLevel::Warn,
+            _ => EventLevel::Info,
+        }
+    }
+}
+
+/// 任务失败的类型，保存在日志的error_class中用于统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// 配置无法解析
+    Config,
+    /// 请求接口或者查询源数据库失败
+    Source,
+    /// 返回数据处理失败
+    Process,
+    /// 写入缓存数据库或者目标数据库失败
+    Write,
+    /// 同步后数据核对不一致
+    Reconcile,
+    /// 用户手动停止
+    Stopped,
+    /// 服务重启导致中断
+    Interrupted,
+}
+
+impl ErrorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorClass::Config => "config",
+            ErrorClass::Source => "source",
+            ErrorClass::Process => "process",
+            ErrorClass::Write => "write",
+            ErrorClass::Reconcile => "reconcile",
+            ErrorClass::Stopped => "stopped",
+            ErrorClass::Interrupted => "interrupted",
+        }
+    }
+}
+
+/// 一次执行中累计的数据量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, TS)]
+#[ts(
+    export,
+    export_to = "ui/api/models/auto-generates/RunMetrics.ts",
+    rename = "RunMetrics"
+)]
+pub struct RunMetrics {
+    pub request_count: i64,
+    pub rows_fetched: i64,
+    pub rows_written: i64,
+    pub rows_rejected: i64,
+    pub bytes: i64,
+}
+
+/// 日志状态变化时需要更新的时间
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunTiming {
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    pub duration_ms: Option<i64>,
+}
+
+impl RunTiming {
+    /// 第一次进入1运行中时记录开始时间，进入2、3、5时记录结束时间与耗时
+    pub fn on_status(status: i32, started_at: Option<NaiveDateTime>, now: NaiveDateTime) -> Self {
+        match status {
+            1 if started_at.is_none() => RunTiming {
+                started_at: Some(now),
+                ..Default::default()
+            },
+            2 | 3 | 5 => RunTiming {
+                finished_at: Some(now),
+                duration_ms: started_at.map(|x| (now - x).num_milliseconds().max(0)),
+                ..Default::default()
+            },
+            _ => RunTiming::default(),
+        }
+    }
+}
+
+/// 推送给查看实时日志的客户端的消息
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(
+    export,
+    export_to = "ui/api/models/auto-generates/RunUpdate.ts",
+    rename = "RunUpdate"
+)]
+pub enum RunUpdate {
+    /// 新的执行事件，与run_event中保存的相同
+    Event {
+        kind: TaskKind,
+        log_id: i32,
+        level: String,
+        message: String,
+        #[ts(type = "string")]
+        create_time: NaiveDateTime,
+    },
+    /// 执行进度，pages为采集已请求的页数或者同步已提交的批次数
+    Progress {
+        kind: TaskKind,
+        log_id: i32,
+        pages: i64,
+        metrics: RunMetrics,
+    },
+    /// 执行结束，之后不会再有这次执行的消息
+    Finished {
+        kind: TaskKind,
+        log_id: i32,
+        status: i32,
+    },
+}
+
+impl RunUpdate {
+    pub fn is_for(&self, kind: TaskKind, log_id: i32) -> bool {
+        let (x, y) = match self {
+            RunUpdate::Event { kind, log_id, .. } => (kind, log_id),
+            RunUpdate::Progress { kind, log_id, .. } => (kind, log_id),
+            RunUpdate::Finished { kind, log_id, .. } => (kind, log_id),
+        };
+        *x == kind && *y == log_id
+    }
+
+    pub fn from_event(kind: TaskKind, event: run_event::Model) -> Self {
+        RunUpdate::Event {
+            kind,
+            log_id: event.log_id,
+            level: event.level,
+            message: event.message,
+            create_time: event.create_time,
+        }
+    }
+}
+
+/// 进程内的执行消息广播，只有当前节点执行的任务会推送给订阅者
+#[derive(Debug, Clone)]
+pub struct RunUpdates(broadcast::Sender<RunUpdate>);
+
+impl RunUpdates {
+    pub fn new(capacity: usize) -> Self {
+        Self(broadcast::channel(capacity).0)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<RunUpdate> {
+        self.0.subscribe()
+    }
+
+    /// 没有订阅者时直接丢弃
+    pub fn publish(&self, update: RunUpdate) {
+        let _ = self.0.send(update);
+    }
+
+    /// 日志更新后推送其中的事件，状态为2、3、5时推送执行结束
+    pub fn publish_log(&self, kind: TaskKind, log_id: i32, status: i32, message: &str) {
+        if self.0.receiver_count() == 0 {
+            return;
+        }
+        let message = message.trim();
+        if !message.is_empty() {
+            self.publish(RunUpdate::Event {
+                kind,
+                log_id,
+                level: EventLevel::from_status(status).as_str().to_string(),
+                message: message.to_string(),
+                create_time: chrono::Local::now().naive_local(),
+            });
+        }
+        if matches!(status, 2 | 3 | 5) {
+            self.publish(RunUpdate::Finished {
+                kind,
+                log_id,
+                status,
+            });
+        }
+    }
+}
+
+pub struct RunEventService;
+
+impl RunEventService {
+    pub async fn add(
+        db: &DbConn,
+        kind: TaskKind,
+        log_id: i32,
+        level: EventLevel,
+        message: &str,
+    ) -> Result<run_event::Model, DbErr> {
+        run_event::ActiveModel {
+            kind: Set(kind.as_str().to_string()),
+            log_id: Set(log_id),
+            level: Set(level.as_str().to_string()),
+            message: Set(message.to_string()),
+            create_time: Set(chrono::Local::now().naive_local()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+    }
+
+    /// 一次执行的全部事件，按发生顺序排列
+    pub async fn list_by_log(
+        db: &DbConn,
+        kind: TaskKind,
+        log_id: i32,
+    ) -> Result<Vec<run_event::Model>, DbErr> {
+        run_event::Entity::find()
+            .filter(run_event::Column::Kind.eq(kind.as_str()))
+            .filter(run_event::Column::LogId.eq(log_id))
+            .order_by_asc(run_event::Column::Id)
+            .all(db)
+            .await
+    }
+
+    /// 一次执行中id大于after_id的事件，用于轮询其他节点执行的任务
+    pub async fn list_after(
+        db: &DbConn,
+        kind: TaskKind,
+        log_id: i32,
+        after_id: i32,
+    ) -> Result<Vec<run_event::Model>, DbErr> {
+        run_event::Entity::find()
+            .filter(run_event::Column::Kind.eq(kind.as_str()))
+            .filter(run_event::Column::LogId.eq(log_id))
+            .filter(run_event::Column::Id.gt(after_id))
+            .order_by_asc(run_event::Column::Id)
+            .all(db)
+            .await
+    }
+
+    /// 采集或者同步日志的状态，日志不存在时返回None
+    pub async fn log_status(
+        db: &DbConn,
+        kind: TaskKind,
+        log_id: i32,
+    ) -> Result<Option<i32>, DbErr> {
+        Ok(match kind {
+            TaskKind::Collect => collect_log::Entity::find_by_id(log_id)
+                .one(db)
+                .await?
+                .map(|x| x.status),
+            TaskKind::Sync => sync_log::Entity::find_by_id(log_id)
+                .one(db)
+                .await?
+                .map(|x| x.status),
+        })
+    }
+
+    pub async fn delete_by_log(
+        db: &DbConn,
+        kind: TaskKind,
+        log_id: i32,
+    ) -> Result<DeleteResult, DbErr> {
+        run_event::Entity::delete_many()
+            .filter(run_event::Column::Kind.eq(kind.as_str()))
+            .filter(run_event::Column::LogId.eq(log_id))
+            .exec(db)
+            .await
+    }
+}
+
+#[test]
+fn test_run_timing() {
+    let start = chrono::NaiveDate::from_ymd_opt(2024, 6, 11)
+        .unwrap()
+        .and_hms_opt(10, 0, 0)
+        .unwrap();
+    let end = start + chrono::Duration::milliseconds(1500);
+
+    assert_eq!(RunTiming::on_status(1, None, start).started_at, Some(start));
+    assert_eq!(
+        RunTiming::on_status(1, Some(start), end),
+        RunTiming::default()
+    );
+    assert_eq!(RunTiming::on_status(4, None, start), RunTiming::default());
+
+    let timing = RunTiming::on_status(2, Some(start), end);
+    assert_eq!(timing.started_at, None);
+    assert_eq!(timing.finished_at, Some(end));
+    assert_eq!(timing.duration_ms, Some(1500));
+
+    // 排队时被停止没有开始时间
+    let timing = RunTiming::on_status(5, None, end);
+    assert_eq!(timing.finished_at, Some(end));
+    assert_eq!(timing.duration_ms, None);
+
+    assert_eq!(EventLevel::from_status(3), EventLevel::Error);
+    assert_eq!(EventLevel::from_status(1), EventLevel::Info);
+}
+
+#[test]
+fn test_run_updates() {
+    let updates = RunUpdates::new(16);
+    let mut receiver = updates.subscribe();
+
+    updates.publish_log(TaskKind::Collect, 1, 1, "\n 开始执行");
+    updates.publish_log(TaskKind::Collect, 1, 2, "");
+
+    let update = receiver.try_recv().unwrap();
+    assert!(update.is_for(TaskKind::Collect, 1));
+    assert!(!update.is_for(TaskKind::Sync, 1));
+    match update {
+        RunUpdate::Event { level, message, .. } => {
+            assert_eq!(level, "info");
+            assert_eq!(message, "开始执行");
+        }
+        _ => panic!("应为执行事件"),
+    }
+    assert_eq!(
+        receiver.try_recv().unwrap(),
+        RunUpdate::Finished {
+            kind: TaskKind::Collect,
+            log_id: 1,
+            status: 2
+        }
+    );
+    assert!(receiver.try_recv().is_err());
+}
@@ -0,0 +1,463 @@
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+
+use chrono::{Duration, Local, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use sea_orm::prelude::DateTime;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::ActiveValue::Set;
+use sea_orm::*;
+use serde::Serialize;
+use sysinfo::System;
+use tokio_cron_scheduler::{Job, JobScheduler, JobSchedulerError};
+use tracing::error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::entity::schedule_state;
+use crate::service::task_executor_service::TaskKind;
+use crate::utils::{format_cron, parse_timezone};
+
+/// 停机期间错过的触发最多补执行的次数
+const MAX_CATCH_UP: usize = 100;
+
+/// 停机期间错过的定时任务的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MisfirePolicy {
+    /// 只补执行一次
+    #[default]
+    Once,
+    /// 每次错过的触发都补执行
+    All,
+    /// 不补执行
+    Skip,
+}
+
+impl MisfirePolicy {
+    pub fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("all") => MisfirePolicy::All,
+            Some("skip") => MisfirePolicy::Skip,
+            _ => MisfirePolicy::Once,
+        }
+    }
+}
+
+/// 当前节点的标识，依次取环境变量NODE_ID、主机名，都没有时随机生成
+/// 重启后标识需要保持不变，才能只把本节点中断的任务日志标记为中断
+pub fn node_id_from_env() -> String {
+    env::var("NODE_ID")
+        .ok()
+        .filter(|x| !x.is_empty())
+        .or_else(System::host_name)
+        .unwrap_or_else(|| Uuid::new_v4().simple().to_string())
+}
+
+/// cron表达式的校验结果
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "ui/api/models/auto-generates/CronPreview.ts")]
+pub struct CronPreview {
+    /// 转化后的rust cron lib格式
+    pub expression: String,
+    pub timezone: String,
+    /// 接下来的触发时间，使用任务所在时区并带有时差
+    pub next_fire_times: Vec<String>,
+}
+
+fn parse_schedule(cron: &str) -> Result<Schedule, DbErr> {
+    let cron = format_cron(cron).map_err(|err| DbErr::Custom(err.to_string()))?;
+    Schedule::from_str(&cron).map_err(|err| DbErr::Custom(err.to_string()))
+}
+
+fn parse_tz(timezone: Option<&str>) -> Result<Option<Tz>, DbErr> {
+    parse_timezone(timezone).map_err(|err| DbErr::Custom(err.to_string()))
+}
+
+fn utc_fire_times<Z: TimeZone>(
+    schedule: &Schedule,
+    after: chrono::DateTime<Z>,
+    until: DateTime,
+    limit: usize,
+) -> Vec<DateTime> {
+    schedule
+        .after(&after)
+        .map(|x| x.naive_utc())
+        .take_while(|x| *x <= until)
+        .take(limit)
+        .collect()
+}
+
+/// (after, until]之间的计划触发时间，最多返回limit个
+/// cron按timezone计算，未配置时按服务器时区计算；after、until与返回的时间都是UTC时间
+pub fn fire_times(
+    cron: &str,
+    timezone: Option<&str>,
+    after: DateTime,
+    until: DateTime,
+    limit: usize,
+) -> Result<Vec<DateTime>, DbErr> {
+    let schedule = parse_schedule(cron)?;
+    let after = Utc.from_utc_datetime(&after);
+
+    Ok(match parse_tz(timezone)? {
+        Some(tz) => utc_fire_times(&schedule, after.with_timezone(&tz), until, limit),
+        None => utc_fire_times(&schedule, after.with_timezone(&Local), until, limit),
+    })
+}
+
+/// (after, until]之间最后一次计划触发时间，时间都是UTC时间，从until往前查找，不受触发次数影响
+pub fn latest_fire_time(
+    cron: &str,
+    timezone: Option<&str>,
+    after: DateTime,
+    until: DateTime,
+) -> Result<Option<DateTime>, DbErr> {
+    let schedule = parse_schedule(cron)?;
+    // 往前查找时不包括起始时间，加一秒以包括until
+    let before = Utc.from_utc_datetime(&(until + Duration::seconds(1)));
+
+    let latest = match parse_tz(timezone)? {
+        Some(tz) => schedule
+            .after(&before.with_timezone(&tz))
+            .next_back()
+            .map(|x| x.naive_utc()),
+        None => schedule
+            .after(&before.with_timezone(&Local))
+            .next_back()
+            .map(|x| x.naive_utc()),
+    };
+    Ok(latest.filter(|x| *x > after))
+}
+
+/// 调度器触发时对应的计划触发时间（UTC），取一分钟内最近的一次，各个节点据此判断是否为同一次触发
+pub fn current_fire_time(
+    cron: &str,
+    timezone: Option<&str>,
+    now: DateTime,
+) -> Result<DateTime, DbErr> {
+    let res = latest_fire_time(cron, timezone, now - Duration::seconds(60), now)?;
+
+    Ok(res.unwrap_or_else(|| now.with_nanosecond(0).unwrap_or(now)))
+}
+
+pub struct ScheduleService;
+
+impl ScheduleService {
+    /// 校验cron与时区，返回接下来count次的触发时间
+    pub fn validate(
+        cron: &str,
+        timezone: Option<&str>,
+        count: usize,
+    ) -> Result<CronPreview, DbErr> {
+        let expression = format_cron(cron).map_err(|err| DbErr::Custom(err.to_string()))?;
+        let schedule = parse_schedule(cron)?;
+        let format = "%Y-%m-%d %H:%M:%S %:z";
+
+        let (timezone, next_fire_times) = match parse_tz(timezone)? {
+            Some(tz) => (
+                tz.name().to_string(),
+                schedule
+                    .upcoming(tz)
+                    .take(count)
+                    .map(|x| x.format(format).to_string())
+                    .collect(),
+            ),
+            None => (
+                "Local".to_string(),
+                schedule
+                    .upcoming(Local)
+                    .take(count)
+                    .map(|x| x.format(format).to_string())
+                    .collect(),
+            ),
+        };
+
+        Ok(CronPreview {
+            expression,
+            timezone,
+            next_fire_times,
+        })
+    }
+
+    /// 创建定时任务，未配置时区时使用服务器时区
+    pub fn cron_job<T>(cron: &str, timezone: Option<&str>, run: T) -> Result<Job, JobSchedulerError>
+    where
+        T: FnMut(Uuid, JobScheduler) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let schedule = format_cron(cron).map_err(|err| {
+            error!("{err}");
+            JobSchedulerError::ParseSchedule
+        })?;
+        let timezone = parse_timezone(timezone).map_err(|err| {
+            error!("{err}");
+            JobSchedulerError::ParseSchedule
+        })?;
+
+        match timezone {
+            Some(tz) => Job::new_async_tz(schedule.as_str(), tz, run),
+            None => Job::new_async_tz(schedule.as_str(), Local, run),
+        }
+    }
+
+    /// 抢占一次触发，多个节点中只有一个会返回true
+    /// 通过带条件的update实现行锁，last_fire_time小于本次触发时间时才能更新成功
+    pub async fn claim(
+        db: &DbConn,
+        kind: TaskKind,
+        config_id: i32,
+        fire_time: DateTime,
+        node_id: &str,
+    ) -> Result<bool, DbErr> {
+        let now = Local::now().naive_local();
+        schedule_state::Entity::insert(schedule_state::ActiveModel {
+            kind: Set(kind.as_str().to_string()),
+            config_id: Set(config_id),
+            last_fire_time: Set(None),
+            node_id: Set(None),
+            update_time: Set(now),
+            create_time: Set(now),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([
+                schedule_state::Column::Kind,
+                schedule_state::Column::ConfigId,
+            ])
+            // MySQL不支持do nothing，用更新为原值代替
+            .update_column(schedule_state::Column::Kind)
+            .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        let res = schedule_state::Entity::update_many()
+            .col_expr(schedule_state::Column::LastFireTime, Expr::value(fire_time))
+            .col_expr(schedule_state::Column::NodeId, Expr::value(node_id))
+            .col_expr(schedule_state::Column::UpdateTime, Expr::value(now))
+            .filter(schedule_state::Column::Kind.eq(kind.as_str()))
+            .filter(schedule_state::Column::ConfigId.eq(config_id))
+            .filter(
+                Condition::any()
+                    .add(schedule_state::Column::LastFireTime.is_null())
+                    .add(schedule_state::Column::LastFireTime.lt(fire_time)),
+            )
+            .exec(db)
+            .await?;
+
+        Ok(res.rows_affected == 1)
+    }
+
+    /// 恢复定时任务时调用，把当前时间记为最近一次触发，暂停期间错过的触发不再执行与补执行
+    pub async fn claim_resume(
+        db: &DbConn,
+        kind: TaskKind,
+        config_id: i32,
+        node_id: &str,
+    ) -> Result<bool, DbErr> {
+        let now = Utc::now().naive_utc();
+        Self::claim(db, kind, config_id, now, node_id).await
+    }
+
+    /// 调度器触发时调用，返回本节点是否需要执行这次触发
+    pub async fn claim_fire(
+        db: &DbConn,
+        kind: TaskKind,
+        config_id: i32,
+        cron: &str,
+        timezone: Option<&str>,
+        node_id: &str,
+    ) -> bool {
+        let res = match current_fire_time(cron, timezone, Utc::now().naive_utc()) {
+            Ok(fire_time) => Self::claim(db, kind, config_id, fire_time, node_id).await,
+            Err(err) => Err(err),
+        };
+
+        res.unwrap_or_else(|err| {
+            error!("{}:{} 定时任务抢占失败 {err}", kind.as_str(), config_id);
+            false
+        })
+    }
+
+    /// 启动时计算停机期间错过的触发，按misfire_policy返回需要补执行的触发时间（UTC）
+    /// 从未被触发过的任务不补执行；其他节点已经补执行过时返回空；
+    /// all最多补执行最早的MAX_CATCH_UP次，抢占时使用的始终是最后一次错过的触发时间
+    pub async fn catch_up(
+        db: &DbConn,
+        kind: TaskKind,
+        config_id: i32,
+        cron: &str,
+        timezone: Option<&str>,
+        policy: MisfirePolicy,
+        node_id: &str,
+    ) -> Result<Vec<DateTime>, DbErr> {
+        let state = schedule_state::Entity::find()
+            .filter(schedule_state::Column::Kind.eq(kind.as_str()))
+            .filter(schedule_state::Column::ConfigId.eq(config_id))
+            .one(db)
+            .await?;
+        let Some(last_fire_time) = state.and_then(|x| x.last_fire_time) else {
+            return Ok(vec![]);
+        };
+
+        let now = Utc::now().naive_utc();
+        let Some(latest) = latest_fire_time(cron, timezone, last_fire_time, now)? else {
+            return Ok(vec![]);
+        };
+        if !Self::claim(db, kind, config_id, latest, node_id).await? {
+            return Ok(vec![]);
+        }
+
+        Ok(match policy {
+            MisfirePolicy::Once => vec![latest],
+            MisfirePolicy::All => fire_times(cron, timezone, last_fire_time, now, MAX_CATCH_UP)?,
+            MisfirePolicy::Skip => vec![],
+        })
+    }
+
+    pub async fn delete_by_config(
+        db: &DbConn,
+        kind: TaskKind,
+        config_id: i32,
+    ) -> Result<DeleteResult, DbErr> {
+        schedule_state::Entity::delete_many()
+            .filter(schedule_state::Column::Kind.eq(kind.as_str()))
+            .filter(schedule_state::Column::ConfigId.eq(config_id))
+            .exec(db)
+            .await
+    }
+}
+
+#[test]
+fn test_fire_times() {
+    let time = |x: &str| DateTime::parse_from_str(x, "%Y-%m-%d %H:%M:%S").unwrap();
+
+    // 上海时间17:00至18:30，即UTC时间09:00至10:30
+    let res = fire_times(
+        "*/30 * * * *",
+        Some("Asia/Shanghai"),
+        time("2024-05-28 09:00:00"),
+        time("2024-05-28 10:30:00"),
+        MAX_CATCH_UP,
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        vec![
+            time("2024-05-28 09:30:00"),
+            time("2024-05-28 10:00:00"),
+            time("2024-05-28 10:30:00"),
+        ]
+    );
+
+    let res = fire_times(
+        "@hourly",
+        None,
+        time("2024-05-28 09:00:00"),
+        time("2024-05-29 09:00:00"),
+        2,
+    )
+    .unwrap();
+    assert_eq!(res.len(), 2);
+
+    // 上海时间每天8点执行，即UTC时间0点
+    let res = fire_times(
+        "0 8 * * *",
+        Some("Asia/Shanghai"),
+        time("2024-05-28 00:00:00"),
+        time("2024-05-30 00:00:00"),
+        MAX_CATCH_UP,
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        vec![time("2024-05-29 00:00:00"), time("2024-05-30 00:00:00")]
+    );
+
+    // 最后一次触发时间不受返回次数的限制
+    assert_eq!(
+        latest_fire_time(
+            "*/5 * * * *",
+            Some("UTC"),
+            time("2024-05-28 00:00:00"),
+            time("2024-05-29 10:03:00"),
+        )
+        .unwrap(),
+        Some(time("2024-05-29 10:00:00"))
+    );
+    assert_eq!(
+        latest_fire_time(
+            "0 0 1 1 *",
+            Some("UTC"),
+            time("2024-05-28 00:00:00"),
+            time("2024-05-29 00:00:00"),
+        )
+        .unwrap(),
+        None
+    );
+
+    assert_eq!(
+        current_fire_time("*/30 * * * *", Some("UTC"), time("2024-05-28 10:30:00")).unwrap(),
+        time("2024-05-28 10:30:00")
+    );
+    assert_eq!(
+        current_fire_time("*/30 * * * *", Some("UTC"), time("2024-05-28 10:30:20")).unwrap(),
+        time("2024-05-28 10:30:00")
+    );
+    assert!(fire_times(
+        "61 * * * *",
+        None,
+        time("2024-05-28 09:00:00"),
+        time("2024-05-28 10:00:00"),
+        1
+    )
+    .is_err());
+    assert_eq!(MisfirePolicy::parse(None), MisfirePolicy::Once);
+}
+
+#[test]
+fn test_validate_cron() {
+    let res = ScheduleService::validate("every 15m", Some("Asia/Shanghai"), 3).unwrap();
+    assert_eq!(res.expression, "0 */15 * * * *");
+    assert_eq!(res.timezone, "Asia/Shanghai");
+    assert_eq!(res.next_fire_times.len(), 3);
+    assert!(res.next_fire_times[0].ends_with("+08:00"));
+
+    assert!(ScheduleService::validate("@daily", Some("Mars/Base"), 3).is_err());
+}
+
+#[tokio::test]
+async fn test_claim_after_resume() -> Result<(), DbErr> {
+    let db = Database::connect("sqlite::memory:").await?;
+    let table = Schema::new(DbBackend::Sqlite)
+        .create_table_from_entity(schedule_state::Entity)
+        .index(
+            sea_query::Index::create()
+                .col(schedule_state::Column::Kind)
+                .col(schedule_state::Column::ConfigId)
+                .unique(),
+        )
+        .to_owned();
+    db.execute(db.get_database_backend().build(&table)).await?;
+
+    let before = Utc::now().naive_utc();
+    assert!(ScheduleService::claim_resume(&db, TaskKind::Collect, 1, "a").await?);
+    // 恢复时记录的是UTC时间，暂停期间错过的触发不能再被抢占
+    let state = schedule_state::Entity::find().one(&db).await?.unwrap();
+    let last_fire_time = state.last_fire_time.unwrap();
+    assert!(last_fire_time >= before && last_fire_time <= Utc::now().naive_utc());
+    assert!(!ScheduleService::claim(&db, TaskKind::Collect, 1, before, "a").await?);
+
+    // 恢复后的下一次触发可以被抢占
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    let cron = "* * * * * *";
+    let timezone = Some("Asia/Shanghai");
+    assert!(ScheduleService::claim_fire(&db, TaskKind::Collect, 1, cron, timezone, "a").await);
+
+    Ok(())
+}
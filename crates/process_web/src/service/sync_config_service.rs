@@ -1,22 +1,137 @@
 use anyhow::Result;
 use chrono::Local;
 use migration::Condition;
-use process_core::db::{Db, DbConfig};
-use process_core::process::{Export, Receive};
+use process_core::db::{
+    checksum_rows, count_rows, execute_sql_in_chunks, find_all_sql, ChunkError, ChunkedExecution,
+    DataSource, Db, DbConfig,
+};
+use process_core::http::generate_sql_list;
+use process_core::process::Receive;
 use sea_orm::prelude::*;
 use sea_orm::ActiveValue::{Set, Unchanged};
 use sea_orm::{IntoActiveModel, QueryOrder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::sync::Arc;
-use tokio_cron_scheduler::{Job, JobSchedulerError};
-use tracing::{debug, error, warn};
+use tokio_cron_scheduler::JobSchedulerError;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+use ts_rs::TS;
+use uuid::fmt::Simple;
 
-use crate::api::common::AppState;
+use crate::api::common::{AppState, LogTask};
 use crate::api::sync_config::ListParams;
 use crate::entity::sync_config::Model;
 use crate::entity::{sync_config, sync_log};
 use crate::service::data_source_list_service::DataSourceListService;
+use crate::service::run_event_service::{ErrorClass, RunMetrics, RunUpdate};
+use crate::service::schedule_service::{MisfirePolicy, ScheduleService};
 use crate::service::sync_log_service::SyncLogService;
-use crate::utils::{format_cron, job_err_to_db_err};
+use crate::service::task_dependency_service::TaskDependencyService;
+use crate::service::task_executor_service::{OverlapPolicy, TaskInfo, TaskKind};
+use crate::service::task_retry_service::{
+    max_attempts_of, parse_retry_config, RetryAttempt, TaskRetryService,
+};
+use crate::utils::job_err_to_db_err;
+
+/// 每批在同一个事务中执行的语句数，停止任务时在两批之间退出
+const CHUNK_SIZE: usize = 500;
+
+/// 同步后核对配置
+/// ```json
+/// {"target_query_sql": "select * from users where update_time >= current_date", "key_columns": ["id"]}
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ReconcileConfig {
+    /// 目标表中本次同步范围的查询语句，为空时核对整个目标表的行数
+    pub target_query_sql: Option<String>,
+    /// 计算校验和的列，为空时只核对行数；
+    /// 校验和需要查询出目标数据的这些列，配置时必须同时配置target_query_sql，避免读取整个目标表
+    #[serde(default)]
+    pub key_columns: Vec<String>,
+}
+
+pub fn parse_reconcile_config(value: &Value) -> anyhow::Result<ReconcileConfig> {
+    let config = serde_json::from_value::<ReconcileConfig>(value.clone())
+        .map_err(|err| anyhow::anyhow!("reconcile_config 无法解析: {err}"))?;
+    let has_query = config
+        .target_query_sql
+        .as_ref()
+        .is_some_and(|x| !x.trim().is_empty());
+    if !config.key_columns.is_empty() && !has_query {
+        return Err(anyhow::anyhow!(
+            "reconcile_config 中配置key_columns时必须配置target_query_sql"
+        ));
+    }
+
+    Ok(config)
+}
+
+#[test]
+fn test_parse_reconcile_config() {
+    let config = parse_reconcile_config(&serde_json::json!({})).unwrap();
+    assert_eq!(config, ReconcileConfig::default());
+    assert!(parse_reconcile_config(&serde_json::json!({"key_columns": ["id"]})).is_err());
+    assert!(parse_reconcile_config(&serde_json::json!({
+        "target_query_sql": " ",
+        "key_columns": ["id"]
+    }))
+    .is_err());
+    assert!(parse_reconcile_config(&serde_json::json!({
+        "target_query_sql": "select id from users where id > 10",
+        "key_columns": ["id"]
+    }))
+    .is_ok());
+}
+
+/// 同步后的核对结果，记录在sync_log中
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(
+    export,
+    export_to = "ui/api/models/auto-generates/SyncReconciliation.ts",
+    rename = "SyncReconciliation"
+)]
+pub struct Reconciliation {
+    pub source_count: u64,
+    pub target_count: u64,
+    pub source_checksum: Option<String>,
+    pub target_checksum: Option<String>,
+    pub matched: bool,
+}
+
+impl Reconciliation {
+    pub fn summary(&self) -> String {
+        let checksum = match (&self.source_checksum, &self.target_checksum) {
+            (Some(source), Some(target)) => format!("，源校验和 {source}，目标校验和 {target}"),
+            _ => String::new(),
+        };
+        let result = match self.matched {
+            true => "数据核对一致",
+            false => "数据核对不一致",
+        };
+        format!(
+            "{result}：源数据 {} 行，目标数据 {} 行{checksum}",
+            self.source_count, self.target_count
+        )
+    }
+}
+
+/// 同步配置预览
+#[derive(Debug, Default, Serialize, TS)]
+#[ts(
+    export,
+    export_to = "ui/api/models/auto-generates/SyncConfigPreview.ts",
+    rename = "SyncConfigPreview"
+)]
+pub struct PreviewResult {
+    /// 添加了行数限制后实际执行的查询语句
+    pub query_sql: String,
+    /// 源数据库中查询到的数据
+    #[ts(type = "any")]
+    pub data: Vec<Value>,
+    /// 按target_query_sql_template生成的语句，不会在目标数据库中执行
+    pub statements: Vec<String>,
+}
 
 pub struct SyncConfigService;
 
@@ -65,6 +180,15 @@ impl SyncConfigService {
     }
 
     pub async fn save(state: Arc<AppState>, id: Option<i32>, data: Model) -> Result<Model, DbErr> {
+        if let Some(cron) = data.cron.as_ref() {
+            ScheduleService::validate(cron, data.timezone.as_deref(), 1)?;
+        }
+        if let Some(x) = data.retry_config.as_ref() {
+            parse_retry_config(x).map_err(|err| DbErr::Custom(err.to_string()))?;
+        }
+        if let Some(x) = data.reconcile_config.as_ref() {
+            parse_reconcile_config(x).map_err(|err| DbErr::Custom(err.to_string()))?;
+        }
         debug!("data: {:?}, id: {:?}", data, id);
         let now = Local::now().naive_local();
 
@@ -78,6 +202,12 @@ impl SyncConfigService {
             target_table_name: Set(data_clone.target_table_name),
             target_query_sql_template: Set(data_clone.target_query_sql_template),
             cron: Set(data_clone.cron),
+            timezone: Set(data_clone.timezone),
+            reconcile_config: Set(data_clone.reconcile_config),
+            priority: Set(data_clone.priority),
+            overlap_policy: Set(data_clone.overlap_policy),
+            misfire_policy: Set(data_clone.misfire_policy),
+            retry_config: Set(data_clone.retry_config),
             ..Default::default()
         };
 
@@ -114,6 +244,95 @@ impl SyncConfigService {
         }
     }
 
+    /// 源或者目标为该数据源的、启用状态为enabled的同步配置
+    async fn find_by_data_source(
+        db: &DbConn,
+        data_source_id: i32,
+        enabled: bool,
+    ) -> Result<Vec<Model>, DbErr> {
+        sync_config::Entity::find()
+            .filter(sync_config::Column::DelFlag.eq(0))
+            .filter(sync_config::Column::Enabled.eq(enabled))
+            .filter(
+                Condition::any()
+                    .add(sync_config::Column::DataSourceId.eq(data_source_id))
+                    .add(sync_config::Column::TargetDataSourceId.eq(data_source_id)),
+            )
+            .all(db)
+            .await
+    }
+
+    /// 数据源维护时暂停所有用到它的同步任务，返回被暂停的配置id
+    pub async fn pause_by_data_source(
+        state: Arc<AppState>,
+        data_source_id: i32,
+    ) -> Result<Vec<i32>, DbErr> {
+        let list = Self::find_by_data_source(&state.conn, data_source_id, true).await?;
+
+        let mut res = vec![];
+        for item in list {
+            Self::pause(state.clone(), item.id).await?;
+            res.push(item.id);
+        }
+        Ok(res)
+    }
+
+    /// 恢复所有用到该数据源且已暂停的同步任务，返回被恢复的配置id
+    pub async fn resume_by_data_source(
+        state: Arc<AppState>,
+        data_source_id: i32,
+    ) -> Result<Vec<i32>, DbErr> {
+        let list = Self::find_by_data_source(&state.conn, data_source_id, false).await?;
+
+        let mut res = vec![];
+        for item in list {
+            Self::resume(state.clone(), item.id).await?;
+            res.push(item.id);
+        }
+        Ok(res)
+    }
+
+    /// 暂停定时任务，移除调度器中的任务并保留cron
+    pub async fn pause(state: Arc<AppState>, id: i32) -> Result<Model, DbErr> {
+        let data = Self::find_by_id(&state.conn, id).await?;
+
+        if let Some(job_id) = data.job_id {
+            state
+                .sched
+                .remove(&job_id)
+                .await
+                .map_err(job_err_to_db_err)?;
+        }
+        let mut active_data = data.into_active_model();
+
+        active_data.enabled = Set(false);
+        active_data.job_id = Set(None);
+        active_data.update_time = Set(Local::now().naive_local());
+        active_data.update(&state.conn).await
+    }
+
+    /// 恢复定时任务，暂停期间错过的触发不补执行
+    pub async fn resume(state: Arc<AppState>, id: i32) -> Result<Model, DbErr> {
+        let data = Self::find_by_id(&state.conn, id).await?;
+        if data.del_flag != 0 {
+            return Err(DbErr::Custom("Cannot find data by id.".to_owned()));
+        }
+        if data.enabled {
+            return Ok(data);
+        }
+
+        ScheduleService::claim_resume(&state.conn, TaskKind::Sync, id, &state.node_id).await?;
+        let job_id = create_job_scheduler(state.clone(), &data)
+            .await
+            .map_err(job_err_to_db_err)?;
+        let mut active_data = data.into_active_model();
+
+        active_data.enabled = Set(true);
+        active_data.job_id = Set(job_id);
+        active_data.update_time = Set(Local::now().naive_local());
+        active_data.update(&state.conn).await
+    }
+
     pub async fn delete(state: Arc<AppState>, id: i32) -> Result<Model, DbErr> {
         let data = sync_config::Entity::find_by_id(id)
             .one(&state.conn)
@@ -132,9 +351,35 @@ impl SyncConfigService {
         active_data.del_flag = Set(1);
         active_data.job_id = Set(None);
 
+        TaskDependencyService::delete_by_config(&state.conn, TaskKind::Sync, id).await?;
+        ScheduleService::delete_by_config(&state.conn, TaskKind::Sync, id).await?;
         active_data.update(&state.conn).await
     }
 
+    /// 只查询前limit行数据并生成目标语句，不写入目标数据库
+    pub async fn preview(db: &DbConn, id: i32, limit: u64) -> Result<PreviewResult, DbErr> {
+        let data = Self::find_by_id(db, id).await?;
+        let data_source: DataSource = DataSourceListService::find_by_id(db, data.data_source_id)
+            .await?
+            .into();
+
+        let query_sql = data_source.database_type.limit_sql(&data.query_sql, limit);
+        let rows = find_all_sql(&data_source, query_sql.clone())
+            .await
+            .map_err(|err| DbErr::Custom(err.to_string()))?;
+        let statements = generate_sql_list(
+            data.target_query_sql_template.trim(),
+            &Value::Array(rows.clone()),
+        )
+        .map_err(|err| DbErr::Custom(err.to_string()))?;
+
+        Ok(PreviewResult {
+            query_sql,
+            data: rows,
+            statements,
+        })
+    }
+
     pub async fn update_job_id_by_id(
         state: Arc<AppState>,
         job_id: Option<Uuid>,
@@ -151,31 +396,110 @@ impl SyncConfigService {
         db_data.update(&state.conn).await
     }
 
-    pub async fn execute_task(state: &Arc<AppState>, data: &Model) {
-        let mut sync_log_model = sync_log::Model {
-            sync_config_id: data.id,
-            status: 0,
-            running_log: String::new(),
-            ..Default::default()
+    /// 创建等待执行的日志并将同步任务提交到执行器
+    /// 上一次任务未结束且overlap_policy为skip时不执行，返回None；
+    /// 作为下游任务被触发时传入上游任务的run_id，失败重试时传入retry
+    pub async fn enqueue_task(
+        state: &Arc<AppState>,
+        data: &Model,
+        run_id: Option<String>,
+        retry: Option<RetryAttempt>,
+    ) -> Result<Option<Simple>, DbErr> {
+        let task_id = Uuid::new_v4().simple();
+        let run_id = match run_id {
+            Some(run_id) => run_id,
+            None => TaskDependencyService::start_run(&state.conn, TaskKind::Sync, data.id).await?,
+        };
+        let (attempt, max_attempts, retry_of) = match retry {
+            Some(x) => (x.attempt, x.max_attempts, Some(x.retry_of)),
+            None => (1, max_attempts_of(data.retry_config.as_ref()), None),
+        };
+        let running_log = match retry {
+            Some(_) => format!("第{attempt}/{max_attempts}次执行，任务已进入执行队列"),
+            None => "任务已进入执行队列".to_string(),
         };
+        let log = SyncLogService::add(
+            &state.conn,
+            sync_log::Model {
+                sync_config_id: data.id,
+                status: 4,
+                task_id: Some(task_id.to_string()),
+                run_id: Some(run_id.clone()),
+                node_id: Some(state.node_id.clone()),
+                attempt: Some(attempt),
+                max_attempts: Some(max_attempts),
+                retry_of,
+                running_log,
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let mut log_task = LogTask::new();
+        log_task.set_log_id(log.id);
+        let token = log_task.token.clone();
+        state.log_task.write().await.insert(task_id, log_task);
+
+        let mut info = TaskInfo::new(
+            task_id.to_string(),
+            TaskKind::Sync,
+            data.id,
+            data.name.clone(),
+        );
+        info.log_id = log.id;
+        info.run_id = run_id.clone();
+        info.priority = data.priority.unwrap_or_default();
+        info.overlap_policy = OverlapPolicy::parse(data.overlap_policy.as_deref());
+        info.resources = vec![
+            format!("data_source:{}", data.data_source_id),
+            format!("data_source:{}", data.target_data_source_id),
+        ];
+
+        // 同步任务在两批语句之间检查是否被停止，保证已执行的语句完整提交或者回滚
+        let st = state.clone();
+        let item = data.clone();
+        let log_id = log.id;
+        let job = Box::pin(async move {
+            SyncConfigService::execute_task(&st, &item, task_id, log_id, token).await;
+            TaskRetryService::on_task_finished(st, TaskKind::Sync, item.id, log_id, run_id).await;
+        });
+        if state.executor.submit(info, job) {
+            return Ok(Some(task_id));
+        }
 
-        match SyncLogService::add(&state.conn, sync_log_model.clone()).await {
-            Ok(db_log_data) => {
-                sync_log_model = db_log_data;
-            }
-            Err(err) => {
-                error!("任务日志添加失败 {err}");
-            }
+        state.log_task.write().await.remove(&task_id);
+        SyncLogService::update_and_publish(
+            state,
+            log.id,
+            sync_log::Model {
+                status: 5,
+                running_log: "上一次任务仍在执行，本次跳过".to_string(),
+                ..Default::default()
+            },
+        )
+        .await?;
+        Ok(None)
+    }
+
+    pub async fn execute_task(
+        state: &Arc<AppState>,
+        data: &Model,
+        task_id: Simple,
+        log_id: i32,
+        token: CancellationToken,
+    ) {
+        // 排队时已被停止
+        if token.is_cancelled() {
+            return;
         }
 
-        let log_id = sync_log_model.id;
         let mut status = 1;
         let model = sync_log::Model {
             status,
             running_log: "开始执行采集任务!".to_string(),
             ..Default::default()
         };
-        if let Some(err) = SyncLogService::update_by_id(&state.conn, log_id, model)
+        if let Some(err) = SyncLogService::update_and_publish(state, log_id, model)
             .await
             .err()
         {
@@ -185,17 +509,65 @@ impl SyncConfigService {
         let mut collect_log_string = String::new();
 
         collect_log_string.push_str(format!("同步配置： {:?}\n", data).as_str());
-        let res = process_data(&state.conn, data).await;
+        let res = process_data(state, data, &token, log_id).await;
+        let mut reconciliation = None;
+        let mut partial = None;
+        let mut error_class = None;
+        // 失败时只知道已提交的语句数
+        let mut rows_fetched = None;
+        let rows_written;
         match res {
-            Ok(_) => {
-                // collect_log_string.push_str(format!("SQL执行成功： {:?}\n", list).as_str());
+            Ok((rows, execution)) if execution.cancelled => {
+                status = 5;
+                error_class = Some(ErrorClass::Stopped);
+                rows_fetched = Some(rows.len() as i64);
+                rows_written = Some(execution.committed as i64);
+                collect_log_string.push_str(
+                    format!("同步任务已停止，已提交 {} 条语句\n", execution.committed).as_str(),
+                );
+            }
+            Ok((rows, execution)) => {
                 status = 2;
-                collect_log_string.push_str("同步任务执行成功!\n");
+                rows_fetched = Some(rows.len() as i64);
+                rows_written = Some(execution.committed as i64);
+                publish_progress(state, log_id, rows.len(), execution.committed);
+                collect_log_string.push_str(
+                    format!("同步任务执行成功，共执行 {} 条语句!\n", execution.committed).as_str(),
+                );
+
+                match reconcile(&state.conn, data, &rows).await {
+                    Ok(Some(res)) => {
+                        collect_log_string.push_str(format!("{}\n", res.summary()).as_str());
+                        if !res.matched {
+                            status = 3;
+                            error_class = Some(ErrorClass::Reconcile);
+                        }
+                        reconciliation = serde_json::to_value(&res).ok();
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        status = 3;
+                        error_class = Some(ErrorClass::Reconcile);
+                        collect_log_string.push_str(format!("数据核对失败: {err}\n").as_str());
+                    }
+                }
+                // 核对失败时数据已全部提交，重试会重复写入
+                if status == 3 {
+                    partial = Some(execution.committed > 0);
+                }
             }
             Err(err) => {
                 let err_str = format!("{}\n", err);
                 collect_log_string.push_str(err_str.as_str());
                 status = 3;
+                // 已有批次提交后失败，重试可能导致数据重复
+                let chunk_error = err.downcast_ref::<ChunkError>();
+                partial = chunk_error.map(|x| x.committed > 0).filter(|x| *x);
+                error_class = match chunk_error {
+                    Some(_) => Some(ErrorClass::Write),
+                    None => Some(ErrorClass::Source),
+                };
+                rows_written = chunk_error.map(|x| x.committed as i64);
                 error!("status: {status} 运行失败；日志更新失败: {err_str}");
             }
         }
@@ -203,26 +575,70 @@ impl SyncConfigService {
         let model = sync_log::Model {
             status,
             running_log: collect_log_string,
+            reconciliation,
+            partial,
+            // 源数据通过一次查询获取
+            request_count: rows_fetched.map(|_| 1),
+            rows_fetched,
+            rows_written,
+            error_class: error_class.map(|x| x.as_str().to_string()),
             ..Default::default()
         };
-        if let Some(err) = SyncLogService::update_by_id(&state.conn, log_id, model)
+        if let Some(err) = SyncLogService::update_and_publish(state, log_id, model)
             .await
             .err()
         {
             error!("status: {status} 运行完毕；日志更新失败: {err}");
         };
+        state.log_task.write().await.remove(&task_id);
     }
 
     /// 初始化所有的同步调度任务
     pub async fn setup_collect_config_cron(state: &Arc<AppState>) -> anyhow::Result<()> {
         let list = sync_config::Entity::find()
             .filter(sync_config::Column::DelFlag.eq(0))
+            .filter(sync_config::Column::Enabled.eq(true))
             .all(&state.conn)
             .await?;
 
         for item in list {
             let state = state.clone();
             let item = item.clone();
+            if let Some(cron) = item.cron.as_ref() {
+                // 旧版本保存的cron可能无法解析，跳过不影响其他任务
+                if let Err(err) = ScheduleService::validate(cron, item.timezone.as_deref(), 1) {
+                    error!("同步配置：{} 定时任务添加失败 {err}", item.name);
+                    continue;
+                }
+                // 补执行失败时只记录日志，不影响服务启动与其他任务
+                let missed = match ScheduleService::catch_up(
+                    &state.conn,
+                    TaskKind::Sync,
+                    item.id,
+                    cron,
+                    item.timezone.as_deref(),
+                    MisfirePolicy::parse(item.misfire_policy.as_deref()),
+                    &state.node_id,
+                )
+                .await
+                {
+                    Ok(x) => x,
+                    Err(err) => {
+                        error!("同步配置：{} 定时任务添加失败 {err}", item.name);
+                        continue;
+                    }
+                };
+                for fire_time in missed {
+                    info!(
+                        "同步配置：{} 补执行 {fire_time} UTC 错过的定时任务",
+                        item.name
+                    );
+                    if let Err(err) = Self::enqueue_task(&state, &item, None, None).await {
+                        error!("同步配置：{} 补执行失败 {err}", item.name);
+                        break;
+                    }
+                }
+            }
             let job_id = create_job_scheduler(state.clone(), &item).await?;
 
             Self::update_job_id_by_id(state, job_id, item.id).await?;
@@ -232,23 +648,103 @@ impl SyncConfigService {
     }
 }
 
-async fn process_data(conn: &DbConn, data: &Model) -> Result<Vec<String>> {
+/// 执行同步，返回从源数据库中查询到的数据与执行结果
+/// 目标语句分批在事务中执行，token取消后在两批之间停止，每批提交后推送进度
+async fn process_data(
+    state: &AppState,
+    data: &Model,
+    token: &CancellationToken,
+    log_id: i32,
+) -> Result<(Vec<Value>, ChunkedExecution)> {
+    let conn = &state.conn;
     let mut db = Db::new();
 
     let data_source = DataSourceListService::find_by_id(conn, data.data_source_id).await?;
-    let target_data_source =
-        DataSourceListService::find_by_id(conn, data.target_data_source_id).await?;
+    let target_data_source: DataSource =
+        DataSourceListService::find_by_id(conn, data.target_data_source_id)
+            .await?
+            .into();
     db.receive(
         data.query_sql.clone(),
         DbConfig {
             db_source_config: data_source.into(),
         },
     )
-    .await?
-    .set_template_string(data.target_query_sql_template.clone())
-    .set_target_db_source_config(target_data_source.into())
-    .export()
-    .await
+    .await?;
+    let sql_list = db
+        .set_template_string(data.target_query_sql_template.clone())
+        .sql_list()?;
+
+    let rows_fetched = match &db.data {
+        Some(Value::Array(rows)) => rows.len(),
+        _ => 0,
+    };
+    let execution =
+        execute_sql_in_chunks(&target_data_source, &sql_list, CHUNK_SIZE, |committed| {
+            if committed > 0 {
+                publish_progress(state, log_id, rows_fetched, committed);
+            }
+            token.is_cancelled()
+        })
+        .await?;
+
+    let rows = match db.data.take() {
+        Some(Value::Array(rows)) => rows,
+        _ => vec![],
+    };
+    Ok((rows, execution))
+}
+
+/// 推送同步进度，pages为已提交的批次数
+fn publish_progress(state: &AppState, log_id: i32, rows_fetched: usize, committed: usize) {
+    state.run_updates.publish(RunUpdate::Progress {
+        kind: TaskKind::Sync,
+        log_id,
+        pages: committed.div_ceil(CHUNK_SIZE) as i64,
+        metrics: RunMetrics {
+            request_count: 1,
+            rows_fetched: rows_fetched as i64,
+            rows_written: committed as i64,
+            ..Default::default()
+        },
+    });
+}
+
+/// 同步完成后核对源数据与目标表中的数据，未配置reconcile_config时不核对
+async fn reconcile(conn: &DbConn, data: &Model, rows: &[Value]) -> Result<Option<Reconciliation>> {
+    let Some(config) = data.reconcile_config.as_ref() else {
+        return Ok(None);
+    };
+    let config = parse_reconcile_config(config)?;
+
+    let target_data_source: DataSource =
+        DataSourceListService::find_by_id(conn, data.target_data_source_id)
+            .await?
+            .into();
+    let target_query_sql = config
+        .target_query_sql
+        .unwrap_or(format!("SELECT * FROM {}", data.target_table_name));
+
+    let mut res = Reconciliation {
+        source_count: rows.len() as u64,
+        target_count: count_rows(&target_data_source, &target_query_sql).await?,
+        ..Default::default()
+    };
+    if !config.key_columns.is_empty() {
+        let target_rows = find_all_sql(
+            &target_data_source,
+            target_data_source
+                .database_type
+                .select_columns_sql(&target_query_sql, &config.key_columns),
+        )
+        .await?;
+        res.source_checksum = Some(checksum_rows(rows, &config.key_columns)?);
+        res.target_checksum = Some(checksum_rows(&target_rows, &config.key_columns)?);
+    }
+    res.matched =
+        res.source_count == res.target_count && res.source_checksum == res.target_checksum;
+
+    Ok(Some(res))
 }
 
 async fn update_job_scheduler(
@@ -256,7 +752,11 @@ async fn update_job_scheduler(
     data: &Model,
     db_data: &Model,
 ) -> Result<Option<Uuid>, JobSchedulerError> {
-    if db_data.cron != data.cron {
+    // 暂停中的任务恢复时再添加
+    if !db_data.enabled {
+        return Ok(None);
+    }
+    if db_data.cron != data.cron || db_data.timezone != data.timezone {
         if let Some(job_id) = db_data.job_id {
             state.sched.remove(&job_id).await?;
         }
@@ -271,17 +771,51 @@ async fn create_job_scheduler(
 ) -> Result<Option<Uuid>, JobSchedulerError> {
     if let Some(cron) = data.cron.as_ref() {
         let sched_state = state.clone();
-        let data = data.clone();
+        let id = data.id;
+        let job_cron = cron.clone();
+        let timezone = data.timezone.clone();
         let job_id = state
             .sched
-            .add(Job::new_async_tz(
-                format_cron(cron.clone()).as_str(),
-                Local::now().timezone(),
+            .add(ScheduleService::cron_job(
+                cron,
+                data.timezone.as_deref(),
                 move |_uuid, mut _l| {
                     let st = sched_state.clone();
-                    let item_c = data.clone();
+                    let cron = job_cron.clone();
+                    let timezone = timezone.clone();
                     Box::pin(async move {
-                        SyncConfigService::execute_task(&st, &item_c).await;
+                        // 其他节点修改、暂停或者删除了配置时，以数据库中的为准
+                        let item = match SyncConfigService::find_by_id(&st.conn, id).await {
+                            Ok(item)
+                                if item.del_flag == 0
+                                    && item.enabled
+                                    && item.cron.as_ref() == Some(&cron)
+                                    && item.timezone == timezone =>
+                            {
+                                item
+                            }
+                            _ => return,
+                        };
+                        if !ScheduleService::claim_fire(
+                            &st.conn,
+                            TaskKind::Sync,
+                            id,
+                            &cron,
+                            timezone.as_deref(),
+                            &st.node_id,
+                        )
+                        .await
+                        {
+                            return;
+                        }
+
+                        match SyncConfigService::enqueue_task(&st, &item, None, None).await {
+                            Ok(Some(_)) => {}
+                            Ok(None) => {
+                                warn!("同步配置：{} 上一次任务仍在执行，本次跳过", item.name)
+                            }
+                            Err(err) => error!("同步配置：{} 任务提交失败 {err}", item.name),
+                        }
                     })
                 },
             )?)
@@ -1,5 +1,8 @@
+use crate::api::common::AppState;
 use crate::api::sync_log::ListParams;
 use crate::entity::{sync_config, sync_log};
+use crate::service::run_event_service::{EventLevel, RunEventService, RunTiming};
+use crate::service::task_executor_service::TaskKind;
 use sea_orm::ActiveValue::{Set, Unchanged};
 use sea_orm::*;
 use serde_json::json;
@@ -66,6 +69,21 @@ impl SyncLogService {
         SyncLogService::save(db, Some(id), data).await
     }
 
+    /// 更新执行中的日志，同时推送给实时查看日志的客户端
+    pub async fn update_and_publish(
+        state: &AppState,
+        id: i32,
+        data: sync_log::Model,
+    ) -> Result<sync_log::Model, DbErr> {
+        let (status, message) = (data.status, data.running_log.clone());
+        let res = Self::update_by_id(&state.conn, id, data).await?;
+        state
+            .run_updates
+            .publish_log(TaskKind::Sync, id, status, &message);
+
+        Ok(res)
+    }
+
     pub async fn save(
         db: &DbConn,
         id: Option<i32>,
@@ -76,7 +94,27 @@ impl SyncLogService {
         let mut active_data = sync_log::ActiveModel {
             ..Default::default()
         };
-        if let Some(id) = id {
+        if data.request_count.is_some() {
+            active_data.request_count = Set(data.request_count);
+        }
+        if data.rows_fetched.is_some() {
+            active_data.rows_fetched = Set(data.rows_fetched);
+        }
+        if data.rows_written.is_some() {
+            active_data.rows_written = Set(data.rows_written);
+        }
+        if data.rows_rejected.is_some() {
+            active_data.rows_rejected = Set(data.rows_rejected);
+        }
+        if data.bytes.is_some() {
+            active_data.bytes = Set(data.bytes);
+        }
+        if data.error_class.is_some() {
+            active_data.error_class = Set(data.error_class);
+        }
+        let message = data.running_log.trim();
+
+        let model = if let Some(id) = id {
             let db_data = sync_log::Entity::find_by_id(id)
                 .one(db)
                 .await?
@@ -84,18 +122,56 @@ impl SyncLogService {
 
             active_data.id = Unchanged(db_data.id);
             active_data.status = Set(data.status);
-            let log = format!("{}\n{}", db_data.running_log, data.running_log);
-            active_data.running_log = Set(log);
+            // 只保存最近的一条，完整的执行过程保存在run_event中
+            if !message.is_empty() {
+                active_data.running_log = Set(message.to_string());
+            }
+            if data.reconciliation.is_some() {
+                active_data.reconciliation = Set(data.reconciliation);
+            }
+            if data.partial.is_some() {
+                active_data.partial = Set(data.partial);
+            }
+            let timing = RunTiming::on_status(data.status, db_data.started_at, now);
+            if timing.started_at.is_some() {
+                active_data.started_at = Set(timing.started_at);
+            }
+            if timing.finished_at.is_some() {
+                active_data.finished_at = Set(timing.finished_at);
+                active_data.duration_ms = Set(timing.duration_ms);
+            }
             active_data.update_time = Set(now);
-            active_data.update(db).await
+            active_data.update(db).await?
         } else {
+            let timing = RunTiming::on_status(data.status, None, now);
             active_data.sync_config_id = Set(data.sync_config_id);
-            active_data.status = Set(0);
-            active_data.running_log = Set(data.running_log);
+            active_data.task_id = Set(data.task_id);
+            active_data.run_id = Set(data.run_id);
+            active_data.node_id = Set(data.node_id);
+            active_data.attempt = Set(data.attempt);
+            active_data.max_attempts = Set(data.max_attempts);
+            active_data.retry_of = Set(data.retry_of);
+            active_data.status = Set(data.status);
+            active_data.running_log = Set(message.to_string());
+            active_data.started_at = Set(timing.started_at);
+            active_data.finished_at = Set(timing.finished_at);
             active_data.create_time = Set(now);
             active_data.update_time = Set(now);
-            active_data.insert(db).await
+            active_data.insert(db).await?
+        };
+
+        if !message.is_empty() {
+            RunEventService::add(
+                db,
+                TaskKind::Sync,
+                model.id,
+                EventLevel::from_status(data.status),
+                message,
+            )
+            .await?;
         }
+
+        Ok(model)
     }
 
     pub async fn delete(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
@@ -105,6 +181,7 @@ impl SyncLogService {
             .ok_or(DbErr::Custom("Cannot find data by id.".to_owned()))
             .map(Into::into)?;
 
+        RunEventService::delete_by_log(db, TaskKind::Sync, id).await?;
         sync_log.delete(db).await
     }
 }
@@ -0,0 +1,526 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::Local;
+use futures_util::future::BoxFuture;
+use once_cell::sync::Lazy;
+use sea_orm::prelude::DateTime;
+use sea_orm::ActiveValue::Set;
+use sea_orm::*;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tracing::{error, warn};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::api::common::AppState;
+use crate::entity::task_dependency::Model;
+use crate::entity::{
+    collect_config, collect_log, sync_config, sync_log, task_dependency, task_run,
+};
+use crate::service::collect_config_service::CollectConfigService;
+use crate::service::run_event_service::RunEventService;
+use crate::service::sync_config_service::SyncConfigService;
+use crate::service::task_executor_service::TaskKind;
+
+const TRIGGER_ON: [&str; 3] = ["success", "failure", "always"];
+
+static TRIGGER_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// 依赖图中的任务
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "ui/api/models/auto-generates/TaskDagNode.ts")]
+pub struct DagNode {
+    pub kind: TaskKind,
+    pub config_id: i32,
+    pub name: String,
+}
+
+/// 任务依赖图
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "ui/api/models/auto-generates/TaskDag.ts")]
+pub struct Dag {
+    pub nodes: Vec<DagNode>,
+    pub edges: Vec<Model>,
+}
+
+/// 一次运行中执行的任务
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "ui/api/models/auto-generates/TaskRunStep.ts")]
+pub struct RunStep {
+    pub kind: TaskKind,
+    pub config_id: i32,
+    pub log_id: i32,
+    pub status: i32,
+    #[ts(type = "string")]
+    pub create_time: DateTime,
+    #[ts(type = "string")]
+    pub update_time: DateTime,
+}
+
+fn node_key(kind: &str, id: i32) -> String {
+    format!("{kind}:{id}")
+}
+
+/// 添加upstream -> downstream这条边后依赖图中是否有环
+pub fn creates_cycle(edges: &[(String, String)], upstream: &str, downstream: &str) -> bool {
+    let mut graph: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (from, to) in edges {
+        graph.entry(from).or_default().push(to);
+    }
+
+    // 从下游出发能回到上游时说明有环
+    let mut stack = vec![downstream];
+    let mut visited = HashSet::new();
+    while let Some(node) = stack.pop() {
+        if node == upstream {
+            return true;
+        }
+        if visited.insert(node) {
+            stack.extend(graph.get(node).into_iter().flatten());
+        }
+    }
+    false
+}
+
+/// 从root出发能到达的全部任务，包括root
+fn reachable(edges: &[(String, String)], root: &str) -> HashSet<String> {
+    let mut graph: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (from, to) in edges {
+        graph.entry(from).or_default().push(to);
+    }
+
+    let mut stack = vec![root];
+    let mut visited = HashSet::new();
+    while let Some(node) = stack.pop() {
+        if visited.insert(node.to_string()) {
+            stack.extend(graph.get(node).into_iter().flatten());
+        }
+    }
+    visited
+}
+
+/// 上游任务最后一次执行的状态是否满足依赖的触发条件
+fn reached(trigger_on: &str, status: Option<i32>) -> bool {
+    matches!(
+        (trigger_on, status),
+        ("success", Some(2)) | ("failure", Some(3)) | ("always", Some(2 | 3))
+    )
+}
+
+#[test]
+fn test_creates_cycle() {
+    let edges = vec![
+        ("collect:1".to_string(), "sync:1".to_string()),
+        ("collect:2".to_string(), "sync:1".to_string()),
+        ("sync:1".to_string(), "collect:3".to_string()),
+    ];
+
+    assert!(creates_cycle(&edges, "collect:1", "collect:1"));
+    assert!(creates_cycle(&edges, "collect:3", "collect:1"));
+    assert!(creates_cycle(&edges, "sync:1", "collect:2"));
+    assert!(!creates_cycle(&edges, "collect:3", "sync:2"));
+    assert!(!creates_cycle(&edges, "collect:1", "collect:3"));
+}
+
+#[test]
+fn test_fan_in() {
+    let edges = vec![
+        ("collect:1".to_string(), "sync:1".to_string()),
+        ("collect:2".to_string(), "sync:1".to_string()),
+        ("collect:3".to_string(), "collect:2".to_string()),
+        ("sync:1".to_string(), "collect:4".to_string()),
+    ];
+
+    let in_run = reachable(&edges, "collect:3");
+    assert!(in_run.contains("collect:3"));
+    assert!(in_run.contains("collect:4"));
+    assert!(!in_run.contains("collect:1"));
+    assert_eq!(reachable(&edges, "collect:1").len(), 3);
+
+    assert!(reached("success", Some(2)));
+    assert!(!reached("success", Some(3)));
+    assert!(reached("failure", Some(3)));
+    assert!(reached("always", Some(3)));
+    assert!(!reached("always", Some(1)));
+    assert!(!reached("always", None));
+}
+
+fn parse_kind(value: &str) -> Result<TaskKind, DbErr> {
+    TaskKind::parse(value).ok_or(DbErr::Custom(format!(
+        "任务类型 {value} 不存在，只能是collect或者sync"
+    )))
+}
+
+pub struct TaskDependencyService;
+
+impl TaskDependencyService {
+    pub async fn list(db: &DbConn) -> Result<Vec<Model>, DbErr> {
+        task_dependency::Entity::find()
+            .order_by_asc(task_dependency::Column::Id)
+            .all(db)
+            .await
+    }
+
+    pub async fn add(db: &DbConn, data: Model) -> Result<Model, DbErr> {
+        let upstream = parse_kind(&data.upstream_kind)?;
+        let downstream = parse_kind(&data.downstream_kind)?;
+        if !TRIGGER_ON.contains(&data.trigger_on.as_str()) {
+            return Err(DbErr::Custom(format!(
+                "trigger_on {} 只能是success、failure或者always",
+                data.trigger_on
+            )));
+        }
+        Self::config_name(db, upstream, data.upstream_id).await?;
+        Self::config_name(db, downstream, data.downstream_id).await?;
+
+        let upstream_key = node_key(upstream.as_str(), data.upstream_id);
+        let downstream_key = node_key(downstream.as_str(), data.downstream_id);
+        let edges = Self::list(db)
+            .await?
+            .iter()
+            .map(|x| {
+                (
+                    node_key(&x.upstream_kind, x.upstream_id),
+                    node_key(&x.downstream_kind, x.downstream_id),
+                )
+            })
+            .collect::<Vec<_>>();
+        if edges
+            .iter()
+            .any(|(from, to)| *from == upstream_key && *to == downstream_key)
+        {
+            return Err(DbErr::Custom("任务依赖已存在".to_owned()));
+        }
+        if creates_cycle(&edges, &upstream_key, &downstream_key) {
+            return Err(DbErr::Custom(format!(
+                "添加 {upstream_key} -> {downstream_key} 后任务依赖中存在循环"
+            )));
+        }
+
+        let now = Local::now().naive_local();
+        task_dependency::ActiveModel {
+            upstream_kind: Set(upstream.as_str().to_string()),
+            upstream_id: Set(data.upstream_id),
+            downstream_kind: Set(downstream.as_str().to_string()),
+            downstream_id: Set(data.downstream_id),
+            trigger_on: Set(data.trigger_on),
+            update_time: Set(now),
+            create_time: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+    }
+
+    pub async fn delete(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+        task_dependency::Entity::delete_by_id(id).exec(db).await
+    }
+
+    /// 删除配置时同时删除与它相关的依赖
+    pub async fn delete_by_config(
+        db: &DbConn,
+        kind: TaskKind,
+        config_id: i32,
+    ) -> Result<DeleteResult, DbErr> {
+        task_dependency::Entity::delete_many()
+            .filter(
+                Condition::any()
+                    .add(
+                        Condition::all()
+                            .add(task_dependency::Column::UpstreamKind.eq(kind.as_str()))
+                            .add(task_dependency::Column::UpstreamId.eq(config_id)),
+                    )
+                    .add(
+                        Condition::all()
+                            .add(task_dependency::Column::DownstreamKind.eq(kind.as_str()))
+                            .add(task_dependency::Column::DownstreamId.eq(config_id)),
+                    ),
+            )
+            .exec(db)
+            .await
+    }
+
+    async fn config_name(db: &DbConn, kind: TaskKind, id: i32) -> Result<String, DbErr> {
+        let name = match kind {
+            TaskKind::Collect => collect_config::Entity::find_by_id(id)
+                .filter(collect_config::Column::DelFlag.eq(0))
+                .one(db)
+                .await?
+                .map(|x| x.name),
+            TaskKind::Sync => sync_config::Entity::find_by_id(id)
+                .filter(sync_config::Column::DelFlag.eq(0))
+                .one(db)
+                .await?
+                .map(|x| x.name),
+        };
+
+        name.ok_or(DbErr::Custom(format!("{} 配置 {id} 不存在", kind.as_str())))
+    }
+
+    pub async fn dag(db: &DbConn) -> Result<Dag, DbErr> {
+        let edges = Self::list(db).await?;
+
+        let mut nodes = vec![];
+        let mut visited = HashSet::new();
+        for edge in edges.iter() {
+            for (kind, id) in [
+                (&edge.upstream_kind, edge.upstream_id),
+                (&edge.downstream_kind, edge.downstream_id),
+            ] {
+                if !visited.insert(node_key(kind, id)) {
+                    continue;
+                }
+                let kind = parse_kind(kind)?;
+                nodes.push(DagNode {
+                    kind,
+                    config_id: id,
+                    name: Self::config_name(db, kind, id).await.unwrap_or_default(),
+                });
+            }
+        }
+
+        Ok(Dag { nodes, edges })
+    }
+
+    /// 定时或者手动执行时开始一次新的运行，任务有下游依赖时记录到task_run中
+    pub async fn start_run(db: &DbConn, kind: TaskKind, config_id: i32) -> Result<String, DbErr> {
+        let run_id = Uuid::new_v4().simple().to_string();
+
+        let downstream_count = task_dependency::Entity::find()
+            .filter(task_dependency::Column::UpstreamKind.eq(kind.as_str()))
+            .filter(task_dependency::Column::UpstreamId.eq(config_id))
+            .count(db)
+            .await?;
+        if downstream_count > 0 {
+            task_run::ActiveModel {
+                run_id: Set(run_id.clone()),
+                kind: Set(kind.as_str().to_string()),
+                config_id: Set(config_id),
+                create_time: Set(Local::now().naive_local()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+
+        Ok(run_id)
+    }
+
+    pub async fn run_list(
+        db: &DbConn,
+        page: u64,
+        page_size: u64,
+    ) -> Result<(Vec<task_run::Model>, u64), DbErr> {
+        let paginator = task_run::Entity::find()
+            .order_by_desc(task_run::Column::Id)
+            .paginate(db, page_size);
+
+        let num_pages = paginator.num_items().await?;
+
+        paginator.fetch_page(page - 1).await.map(|p| (p, num_pages))
+    }
+
+    /// 一次运行中执行过的全部任务，按开始时间排序
+    pub async fn run_steps(db: &DbConn, run_id: &str) -> Result<Vec<RunStep>, DbErr> {
+        let mut steps = collect_log::Entity::find()
+            .filter(collect_log::Column::RunId.eq(run_id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|x| RunStep {
+                kind: TaskKind::Collect,
+                config_id: x.collect_config_id.unwrap_or_default(),
+                log_id: x.id,
+                status: x.status,
+                create_time: x.create_time,
+                update_time: x.update_time,
+            })
+            .collect::<Vec<_>>();
+
+        steps.extend(
+            sync_log::Entity::find()
+                .filter(sync_log::Column::RunId.eq(run_id))
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|x| RunStep {
+                    kind: TaskKind::Sync,
+                    config_id: x.sync_config_id,
+                    log_id: x.id,
+                    status: x.status,
+                    create_time: x.create_time,
+                    update_time: x.update_time,
+                }),
+        );
+        steps.sort_by_key(|x| x.create_time);
+
+        Ok(steps)
+    }
+
+    /// 任务结束后按日志状态触发下游任务，下游任务使用相同的run_id；被停止或者中断的任务不触发
+    /// 与enqueue_task相互调用，返回BoxFuture避免编译器推断Send时出现循环
+    pub fn on_task_finished(
+        state: Arc<AppState>,
+        kind: TaskKind,
+        config_id: i32,
+        log_id: i32,
+        run_id: String,
+    ) -> BoxFuture<'static, ()> {
+        Box::pin(async move {
+            if let Err(err) =
+                Self::trigger_downstream(&state, kind, config_id, log_id, run_id).await
+            {
+                error!("{}:{} 触发下游任务失败 {err}", kind.as_str(), config_id);
+            }
+        })
+    }
+
+    /// 任务在这次运行中最后一次执行的状态，没有执行过时返回None
+    async fn last_status(
+        db: &DbConn,
+        kind: TaskKind,
+        config_id: i32,
+        run_id: &str,
+    ) -> Result<Option<i32>, DbErr> {
+        let status = match kind {
+            TaskKind::Collect => collect_log::Entity::find()
+                .filter(collect_log::Column::CollectConfigId.eq(config_id))
+                .filter(collect_log::Column::RunId.eq(run_id))
+                .order_by_desc(collect_log::Column::Id)
+                .one(db)
+                .await?
+                .map(|x| x.status),
+            TaskKind::Sync => sync_log::Entity::find()
+                .filter(sync_log::Column::SyncConfigId.eq(config_id))
+                .filter(sync_log::Column::RunId.eq(run_id))
+                .order_by_desc(sync_log::Column::Id)
+                .one(db)
+                .await?
+                .map(|x| x.status),
+        };
+        Ok(status)
+    }
+
+    /// 下游任务在这次运行中的全部上游任务都满足触发条件后才触发，
+    /// 不在这次运行中的上游任务（从开始运行的任务出发无法到达）不需要等待
+    async fn trigger_downstream(
+        state: &Arc<AppState>,
+        kind: TaskKind,
+        config_id: i32,
+        log_id: i32,
+        run_id: String,
+    ) -> Result<(), DbErr> {
+        let status = RunEventService::log_status(&state.conn, kind, log_id).await?;
+        if !matches!(status, Some(2 | 3)) {
+            return Ok(());
+        }
+
+        let edges = Self::list(&state.conn).await?;
+        let root = task_run::Entity::find()
+            .filter(task_run::Column::RunId.eq(&run_id))
+            .one(&state.conn)
+            .await?
+            .map(|x| node_key(&x.kind, x.config_id))
+            .unwrap_or(node_key(kind.as_str(), config_id));
+        let keys = edges
+            .iter()
+            .map(|x| {
+                (
+                    node_key(&x.upstream_kind, x.upstream_id),
+                    node_key(&x.downstream_kind, x.downstream_id),
+                )
+            })
+            .collect::<Vec<_>>();
+        let in_run = reachable(&keys, &root);
+
+        // 多个上游任务同时结束时只触发一次下游任务
+        let _guard = TRIGGER_LOCK.lock().await;
+        for edge in edges
+            .iter()
+            .filter(|x| x.upstream_kind == kind.as_str() && x.upstream_id == config_id)
+        {
+            let downstream_kind = parse_kind(&edge.downstream_kind)?;
+            let downstream = node_key(&edge.downstream_kind, edge.downstream_id);
+
+            let mut ready = true;
+            for upstream in edges.iter().filter(|x| {
+                node_key(&x.downstream_kind, x.downstream_id) == downstream
+                    && in_run.contains(&node_key(&x.upstream_kind, x.upstream_id))
+            }) {
+                let status = Self::last_status(
+                    &state.conn,
+                    parse_kind(&upstream.upstream_kind)?,
+                    upstream.upstream_id,
+                    &run_id,
+                )
+                .await?;
+                if !reached(&upstream.trigger_on, status) {
+                    ready = false;
+                    break;
+                }
+            }
+            if !ready {
+                continue;
+            }
+            if Self::last_status(&state.conn, downstream_kind, edge.downstream_id, &run_id)
+                .await?
+                .is_some()
+            {
+                continue;
+            }
+
+            // 暂停或者已删除的下游任务不执行
+            let res = match downstream_kind {
+                TaskKind::Collect => {
+                    match collect_config::Entity::find_by_id(edge.downstream_id)
+                        .one(&state.conn)
+                        .await?
+                    {
+                        Some(data) if data.enabled && data.del_flag == 0 => {
+                            CollectConfigService::enqueue_task(
+                                state,
+                                &data,
+                                Some(run_id.clone()),
+                                None,
+                            )
+                            .await
+                        }
+                        _ => {
+                            warn!("下游任务 {downstream} 已暂停或者已删除，本次跳过");
+                            continue;
+                        }
+                    }
+                }
+                TaskKind::Sync => {
+                    match sync_config::Entity::find_by_id(edge.downstream_id)
+                        .one(&state.conn)
+                        .await?
+                    {
+                        Some(data) if data.enabled && data.del_flag == 0 => {
+                            SyncConfigService::enqueue_task(
+                                state,
+                                &data,
+                                Some(run_id.clone()),
+                                None,
+                            )
+                            .await
+                        }
+                        _ => {
+                            warn!("下游任务 {downstream} 已暂停或者已删除，本次跳过");
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            match res {
+                Ok(Some(_)) => {}
+                Ok(None) => warn!("下游任务 {downstream} 上一次任务仍在执行，本次跳过"),
+                Err(err) => error!("下游任务 {downstream} 提交失败 {err}"),
+            }
+        }
+
+        Ok(())
+    }
+}
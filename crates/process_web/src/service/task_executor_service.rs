@@ -0,0 +1,332 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use chrono::Local;
+use sea_orm::prelude::DateTime;
+use serde::Serialize;
+use tracing::{debug, error};
+use ts_rs::TS;
+
+/// 提交到执行器中的任务
+pub type Job = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "ui/api/models/auto-generates/TaskKind.ts")]
+pub enum TaskKind {
+    Collect,
+    Sync,
+}
+
+impl TaskKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskKind::Collect => "collect",
+            TaskKind::Sync => "sync",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "collect" => Some(TaskKind::Collect),
+            "sync" => Some(TaskKind::Sync),
+            _ => None,
+        }
+    }
+}
+
+/// 同一个配置的上一次任务还未结束时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverlapPolicy {
+    /// 跳过本次任务
+    Skip,
+    /// 排队等待上一次任务结束
+    #[default]
+    Queue,
+    /// 允许同时执行
+    Allow,
+}
+
+impl OverlapPolicy {
+    pub fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("skip") => OverlapPolicy::Skip,
+            Some("allow") => OverlapPolicy::Allow,
+            _ => OverlapPolicy::Queue,
+        }
+    }
+}
+
+/// 执行器配置
+#[derive(Debug, Clone)]
+pub struct ExecutorConfig {
+    /// 同时执行的任务数
+    pub workers: usize,
+    /// 同一个数据源同时执行的任务数
+    pub data_source_limit: usize,
+    /// 同一个上游host同时执行的任务数
+    pub host_limit: usize,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self {
+            workers: 10,
+            data_source_limit: 2,
+            host_limit: 2,
+        }
+    }
+}
+
+impl ExecutorConfig {
+    /// 从环境变量TASK_WORKERS、TASK_DATA_SOURCE_LIMIT、TASK_HOST_LIMIT中读取，未设置时使用默认值
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let read = |name: &str, default: usize| {
+            env::var(name)
+                .ok()
+                .and_then(|x| x.parse::<usize>().ok())
+                .filter(|x| *x > 0)
+                .unwrap_or(default)
+        };
+
+        Self {
+            workers: read("TASK_WORKERS", default.workers),
+            data_source_limit: read("TASK_DATA_SOURCE_LIMIT", default.data_source_limit),
+            host_limit: read("TASK_HOST_LIMIT", default.host_limit),
+        }
+    }
+}
+
+/// 执行器中的任务信息
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "ui/api/models/auto-generates/TaskInfo.ts")]
+pub struct TaskInfo {
+    pub task_id: String,
+    pub kind: TaskKind,
+    pub config_id: i32,
+    pub name: String,
+    pub log_id: i32,
+    /// 依赖链中的任务使用相同的run_id
+    pub run_id: String,
+    /// 数值越大越先执行，相同优先级按提交顺序执行
+    pub priority: i32,
+    /// 任务占用的资源，如data_source:1、host:example.com
+    pub resources: Vec<String>,
+    pub running: bool,
+    #[ts(type = "string")]
+    pub queued_at: DateTime,
+    #[ts(type = "string | null")]
+    pub started_at: Option<DateTime>,
+    #[serde(skip)]
+    #[ts(skip)]
+    pub overlap_policy: OverlapPolicy,
+}
+
+impl TaskInfo {
+    pub fn new(task_id: String, kind: TaskKind, config_id: i32, name: String) -> Self {
+        Self {
+            task_id,
+            kind,
+            config_id,
+            name,
+            log_id: -1,
+            run_id: String::new(),
+            priority: 0,
+            resources: vec![],
+            running: false,
+            queued_at: Local::now().naive_local(),
+            started_at: None,
+            overlap_policy: OverlapPolicy::default(),
+        }
+    }
+
+    /// 任务需要占用的全部资源，未允许同时执行时同一个配置也作为资源，同时只能有一个任务占用
+    fn all_resources(&self) -> Vec<String> {
+        let mut resources = self.resources.clone();
+        if self.overlap_policy != OverlapPolicy::Allow {
+            resources.push(format!("{}:{}", self.kind.as_str(), self.config_id));
+        }
+        resources.sort();
+        resources.dedup();
+        resources
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    queue: Vec<(TaskInfo, Job)>,
+    running: Vec<TaskInfo>,
+    resources: HashMap<String, usize>,
+}
+
+/// 全局任务执行器，所有定时与手动执行的采集、同步任务都通过它排队执行
+pub struct TaskExecutor {
+    config: ExecutorConfig,
+    inner: Mutex<Inner>,
+}
+
+impl TaskExecutor {
+    pub fn new(config: ExecutorConfig) -> Self {
+        Self {
+            config,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    /// 提交任务，同一个配置的任务已在执行或排队且overlap_policy为skip时返回false
+    pub fn submit(self: &Arc<Self>, info: TaskInfo, job: Job) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if info.overlap_policy == OverlapPolicy::Skip
+            && inner
+                .running
+                .iter()
+                .chain(inner.queue.iter().map(|(x, _)| x))
+                .any(|x| x.kind == info.kind && x.config_id == info.config_id)
+        {
+            return false;
+        }
+
+        debug!("任务进入队列 {:?}", info);
+        inner.queue.push((info, job));
+        // 稳定排序，相同优先级保持提交顺序
+        inner.queue.sort_by_key(|(x, _)| Reverse(x.priority));
+        drop(inner);
+
+        self.dispatch();
+        true
+    }
+
+    /// 移除还在排队的任务，任务已开始执行时返回false
+    pub fn cancel(&self, task_id: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let len = inner.queue.len();
+        inner.queue.retain(|(x, _)| x.task_id != task_id);
+
+        inner.queue.len() != len
+    }
+
+    /// 正在执行的任务在前，排队的任务按执行顺序在后
+    pub fn list(&self) -> Vec<TaskInfo> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .running
+            .iter()
+            .cloned()
+            .chain(inner.queue.iter().map(|(x, _)| x.clone()))
+            .collect()
+    }
+
+    fn limit(&self, resource: &str) -> usize {
+        match resource.split_once(':') {
+            Some(("data_source", _)) => self.config.data_source_limit,
+            Some(("host", _)) => self.config.host_limit,
+            _ => 1,
+        }
+    }
+
+    /// 在空闲的线程中执行排在最前面且资源未被占满的任务
+    fn dispatch(self: &Arc<Self>) {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        while inner.running.len() < self.config.workers {
+            let Some(index) = inner.queue.iter().position(|(info, _)| {
+                info.all_resources()
+                    .iter()
+                    .all(|x| inner.resources.get(x).copied().unwrap_or(0) < self.limit(x))
+            }) else {
+                break;
+            };
+
+            let (mut info, job) = inner.queue.remove(index);
+            for resource in info.all_resources() {
+                *inner.resources.entry(resource).or_default() += 1;
+            }
+            info.running = true;
+            info.started_at = Some(Local::now().naive_local());
+            let task_id = info.task_id.clone();
+            inner.running.push(info);
+
+            let executor = self.clone();
+            tokio::spawn(async move {
+                // 任务panic时也要释放占用的资源
+                if let Err(err) = tokio::spawn(job).await {
+                    error!("任务 {task_id} 执行异常: {err}");
+                }
+                executor.finish(&task_id);
+            });
+        }
+    }
+
+    fn finish(self: &Arc<Self>, task_id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(index) = inner.running.iter().position(|x| x.task_id == task_id) {
+            let info = inner.running.remove(index);
+            for resource in info.all_resources() {
+                if let Some(count) = inner.resources.get_mut(&resource) {
+                    *count -= 1;
+                    if *count == 0 {
+                        inner.resources.remove(&resource);
+                    }
+                }
+            }
+        }
+        drop(inner);
+
+        self.dispatch();
+    }
+}
+
+#[tokio::test]
+async fn test_task_executor() {
+    let executor = Arc::new(TaskExecutor::new(ExecutorConfig {
+        workers: 1,
+        ..Default::default()
+    }));
+    let order = Arc::new(Mutex::new(vec![]));
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+
+    let task = |id: &str, config_id: i32, priority: i32| {
+        let mut info = TaskInfo::new(id.to_string(), TaskKind::Collect, config_id, id.to_string());
+        info.priority = priority;
+        info
+    };
+    let record = |id: &'static str| -> Job {
+        let order = order.clone();
+        Box::pin(async move { order.lock().unwrap().push(id) })
+    };
+
+    let first = order.clone();
+    assert!(executor.submit(
+        task("a", 1, 0),
+        Box::pin(async move {
+            let _ = rx.await;
+            first.lock().unwrap().push("a");
+        })
+    ));
+    assert!(executor.submit(task("b", 2, 0), record("b")));
+    assert!(executor.submit(task("c", 3, 5), record("c")));
+    assert!(executor.submit(task("d", 4, 0), record("d")));
+
+    let mut overlap = task("e", 1, 0);
+    overlap.overlap_policy = OverlapPolicy::Skip;
+    assert!(!executor.submit(overlap, record("e")));
+
+    let list = executor.list();
+    assert_eq!(
+        list.iter().map(|x| x.task_id.as_str()).collect::<Vec<_>>(),
+        vec!["a", "c", "b", "d"]
+    );
+    assert!(list[0].running && !list[1].running);
+    assert!(executor.cancel("b"));
+
+    tx.send(()).unwrap();
+    while !executor.list().is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(*order.lock().unwrap(), vec!["a", "c", "d"]);
+}
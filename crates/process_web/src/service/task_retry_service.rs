@@ -0,0 +1,299 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use futures_util::future::BoxFuture;
+use sea_orm::{DbErr, EntityTrait};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{error, info, warn};
+
+use crate::api::common::AppState;
+use crate::entity::{collect_config, sync_config};
+use crate::service::collect_config_service::CollectConfigService;
+use crate::service::collect_log_service::CollectLogService;
+use crate::service::run_event_service::ErrorClass;
+use crate::service::sync_config_service::SyncConfigService;
+use crate::service::sync_log_service::SyncLogService;
+use crate::service::task_dependency_service::TaskDependencyService;
+use crate::service::task_executor_service::TaskKind;
+
+/// 失败后的重试策略，采集写入缓存数据库失败的语句也按此策略重试
+/// ```json
+/// {"max_attempts": 3, "delay_secs": 60, "backoff": 2, "max_delay_secs": 3600, "retry_on_partial": false}
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// 最多执行的次数，包括第一次执行
+    pub max_attempts: i32,
+    /// 第一次重试前等待的秒数
+    pub delay_secs: u64,
+    /// 每次重试等待时间的倍数
+    pub backoff: f64,
+    /// 最长等待的秒数
+    pub max_delay_secs: u64,
+    /// 部分数据已写入后失败时是否重试，重试可能导致数据重复
+    pub retry_on_partial: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            delay_secs: 60,
+            backoff: 2.0,
+            max_delay_secs: 3600,
+            retry_on_partial: false,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// 第attempt次执行失败后，下一次重试前等待的时间
+    pub fn delay(&self, attempt: i32) -> Duration {
+        let secs = self.delay_secs as f64 * self.backoff.powi(attempt.max(1) - 1);
+
+        Duration::from_secs(secs.min(self.max_delay_secs as f64) as u64)
+    }
+
+    /// 状态为3或者部分写入失败时重试，部分写入失败需要开启retry_on_partial
+    pub fn should_retry(&self, status: i32, partial: bool, attempt: i32) -> bool {
+        let failed = status == 3 || (status == 2 && partial);
+
+        failed && (!partial || self.retry_on_partial) && attempt < self.max_attempts
+    }
+}
+
+pub fn parse_retry_config(value: &Value) -> anyhow::Result<RetryConfig> {
+    let config = serde_json::from_value::<RetryConfig>(value.clone())
+        .map_err(|err| anyhow!("retry_config 无法解析: {err}"))?;
+    if config.max_attempts < 1 || config.backoff < 1.0 {
+        return Err(anyhow!(
+            "retry_config 中max_attempts不能小于1，backoff不能小于1"
+        ));
+    }
+
+    Ok(config)
+}
+
+/// 配置中的最多执行次数，未配置重试时为1
+pub fn max_attempts_of(value: Option<&Value>) -> i32 {
+    value
+        .and_then(|x| parse_retry_config(x).ok())
+        .map(|x| x.max_attempts)
+        .unwrap_or(1)
+}
+
+/// 重试时的执行次数，日志中显示为attempt/max_attempts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryAttempt {
+    pub attempt: i32,
+    pub max_attempts: i32,
+    /// 第一次执行的日志id
+    pub retry_of: i32,
+}
+
+pub struct TaskRetryService;
+
+impl TaskRetryService {
+    /// 任务结束后调用，需要重试时延迟后重新提交，否则触发下游任务
+    /// 重试中的任务等最后一次执行结束后才触发下游，下游任务使用相同的run_id
+    pub fn on_task_finished(
+        state: Arc<AppState>,
+        kind: TaskKind,
+        config_id: i32,
+        log_id: i32,
+        run_id: String,
+    ) -> BoxFuture<'static, ()> {
+        Box::pin(async move {
+            match Self::schedule_retry(&state, kind, config_id, log_id, &run_id).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    TaskDependencyService::on_task_finished(state, kind, config_id, log_id, run_id)
+                        .await
+                }
+                Err(err) => {
+                    error!("{}:{} 重试任务提交失败 {err}", kind.as_str(), config_id);
+                    TaskDependencyService::on_task_finished(state, kind, config_id, log_id, run_id)
+                        .await
+                }
+            }
+        })
+    }
+
+    /// 按日志状态与配置中的重试策略判断是否重试，需要重试时返回true
+    async fn schedule_retry(
+        state: &Arc<AppState>,
+        kind: TaskKind,
+        config_id: i32,
+        log_id: i32,
+        run_id: &str,
+    ) -> Result<bool, DbErr> {
+        let (status, partial, attempt, retry_of, error_class, retry_config) = match kind {
+            TaskKind::Collect => {
+                let log = CollectLogService::find_by_id(&state.conn, log_id).await?;
+                let data = CollectConfigService::find_by_id(&state.conn, config_id).await?;
+                (
+                    log.status,
+                    log.partial.unwrap_or_default(),
+                    log.attempt.unwrap_or(1),
+                    log.retry_of.unwrap_or(log.id),
+                    log.error_class,
+                    data.retry_config,
+                )
+            }
+            TaskKind::Sync => {
+                let log = SyncLogService::find_by_id(&state.conn, log_id).await?;
+                let data = SyncConfigService::find_by_id(&state.conn, config_id).await?;
+                (
+                    log.status,
+                    log.partial.unwrap_or_default(),
+                    log.attempt.unwrap_or(1),
+                    log.retry_of.unwrap_or(log.id),
+                    log.error_class,
+                    data.retry_config,
+                )
+            }
+        };
+        let Some(retry_config) = retry_config else {
+            return Ok(false);
+        };
+        // 核对不一致时数据已经写入，重新执行无法修复
+        if error_class.as_deref() == Some(ErrorClass::Reconcile.as_str()) {
+            return Ok(false);
+        }
+        let config =
+            parse_retry_config(&retry_config).map_err(|err| DbErr::Custom(err.to_string()))?;
+        if !config.should_retry(status, partial, attempt) {
+            return Ok(false);
+        }
+
+        let delay = config.delay(attempt);
+        let next = RetryAttempt {
+            attempt: attempt + 1,
+            max_attempts: config.max_attempts,
+            retry_of,
+        };
+        info!(
+            "{}:{} 第{attempt}次执行失败，{}秒后进行第{}/{}次执行",
+            kind.as_str(),
+            config_id,
+            delay.as_secs(),
+            next.attempt,
+            next.max_attempts
+        );
+
+        let state = state.clone();
+        let run_id = run_id.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            // 没有重试时按最后一次执行的日志触发下游任务
+            match Self::enqueue_retry(&state, kind, config_id, run_id.clone(), next).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    TaskDependencyService::on_task_finished(state, kind, config_id, log_id, run_id)
+                        .await
+                }
+                Err(err) => {
+                    error!("{}:{} 重试任务提交失败 {err}", kind.as_str(), config_id);
+                    TaskDependencyService::on_task_finished(state, kind, config_id, log_id, run_id)
+                        .await
+                }
+            }
+        });
+
+        Ok(true)
+    }
+
+    /// 提交重试任务，等待期间任务被暂停、删除或者上一次任务仍在执行时跳过并返回false
+    async fn enqueue_retry(
+        state: &Arc<AppState>,
+        kind: TaskKind,
+        config_id: i32,
+        run_id: String,
+        retry: RetryAttempt,
+    ) -> Result<bool, DbErr> {
+        let res = match kind {
+            TaskKind::Collect => {
+                match collect_config::Entity::find_by_id(config_id)
+                    .one(&state.conn)
+                    .await?
+                {
+                    Some(data) if data.enabled && data.del_flag == 0 => {
+                        CollectConfigService::enqueue_task(state, &data, Some(run_id), Some(retry))
+                            .await?
+                    }
+                    _ => {
+                        warn!(
+                            "{}:{} 已暂停或者已删除，本次重试跳过",
+                            kind.as_str(),
+                            config_id
+                        );
+                        return Ok(false);
+                    }
+                }
+            }
+            TaskKind::Sync => {
+                match sync_config::Entity::find_by_id(config_id)
+                    .one(&state.conn)
+                    .await?
+                {
+                    Some(data) if data.enabled && data.del_flag == 0 => {
+                        SyncConfigService::enqueue_task(state, &data, Some(run_id), Some(retry))
+                            .await?
+                    }
+                    _ => {
+                        warn!(
+                            "{}:{} 已暂停或者已删除，本次重试跳过",
+                            kind.as_str(),
+                            config_id
+                        );
+                        return Ok(false);
+                    }
+                }
+            }
+        };
+        if res.is_none() {
+            warn!(
+                "{}:{} 上一次任务仍在执行，本次重试跳过",
+                kind.as_str(),
+                config_id
+            );
+        }
+
+        Ok(res.is_some())
+    }
+}
+
+#[test]
+fn test_retry_config() {
+    let config = parse_retry_config(&serde_json::json!({
+        "max_attempts": 3,
+        "delay_secs": 10,
+        "max_delay_secs": 30
+    }))
+    .unwrap();
+
+    assert_eq!(config.delay(1), Duration::from_secs(10));
+    assert_eq!(config.delay(2), Duration::from_secs(20));
+    assert_eq!(config.delay(3), Duration::from_secs(30));
+
+    assert!(config.should_retry(3, false, 1));
+    assert!(config.should_retry(3, false, 2));
+    assert!(!config.should_retry(3, false, 3));
+    assert!(!config.should_retry(2, false, 1));
+    assert!(!config.should_retry(5, false, 1));
+    assert!(!config.should_retry(2, true, 1));
+    assert!(!config.should_retry(3, true, 1));
+
+    let config = RetryConfig {
+        retry_on_partial: true,
+        ..config
+    };
+    assert!(config.should_retry(2, true, 1));
+    assert!(config.should_retry(3, true, 1));
+
+    assert!(parse_retry_config(&serde_json::json!({"max_attempts": 0})).is_err());
+    assert_eq!(max_attempts_of(None), 1);
+}